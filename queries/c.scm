@@ -0,0 +1,14 @@
+;; Function-like macros
+(preproc_function_def) @macro
+
+;; Functions: definitions and prototypes (non-function declarations are skipped)
+(function_definition) @function
+(declaration) @function
+
+;; Named structs, unions and enums with a body
+(struct_specifier name: (_) body: (_)) @record
+(union_specifier name: (_) body: (_)) @record
+(enum_specifier name: (_) body: (_)) @record
+
+;; Typedefs
+(type_definition) @typedef
@@ -0,0 +1,3 @@
+;; #include "local.h" / #include <system.h>
+(preproc_include
+  path: [(string_literal) (system_lib_string)] @import_source)
@@ -0,0 +1,19 @@
+;; Function-like macros
+(preproc_function_def) @macro
+
+;; Functions: definitions and prototypes (non-function declarations are skipped)
+(function_definition) @function
+(declaration) @function
+
+;; Named classes, structs, unions and enums with a body
+(class_specifier name: (_) body: (_)) @record
+(struct_specifier name: (_) body: (_)) @record
+(union_specifier name: (_) body: (_)) @record
+(enum_specifier name: (_) body: (_)) @record
+
+;; Typedefs and aliases
+(type_definition) @typedef
+(alias_declaration) @typedef
+
+;; Namespaces
+(namespace_definition name: (_) @namespace)
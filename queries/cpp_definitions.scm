@@ -0,0 +1,35 @@
+(function_definition
+  declarator: (function_declarator declarator: (identifier) @name)) @definition.function
+(function_definition
+  declarator: (pointer_declarator
+    declarator: (function_declarator declarator: (identifier) @name))) @definition.function
+(declaration
+  declarator: (function_declarator declarator: (identifier) @name)) @definition.prototype
+(declaration
+  declarator: (pointer_declarator
+    declarator: (function_declarator declarator: (identifier) @name))) @definition.prototype
+
+;; Methods: inline in the class body, declared there, or defined as `Widget::draw`
+(function_definition
+  declarator: (function_declarator declarator: (field_identifier) @name)) @definition.method
+(field_declaration
+  declarator: (function_declarator declarator: (field_identifier) @name)) @definition.method
+(function_definition
+  declarator: (function_declarator
+    declarator: (qualified_identifier name: (identifier) @name))) @definition.method
+(function_definition
+  declarator: (function_declarator
+    declarator: (qualified_identifier
+      name: (qualified_identifier name: (identifier) @name)))) @definition.method
+
+(namespace_definition name: (_) @name) @definition.namespace
+(class_specifier name: (type_identifier) @name body: (_)) @definition.class
+(struct_specifier name: (type_identifier) @name body: (_)) @definition.struct
+(union_specifier name: (type_identifier) @name body: (_)) @definition.union
+(enum_specifier name: (type_identifier) @name body: (_)) @definition.enum
+(enumerator name: (identifier) @name) @definition.enumerator
+(type_definition declarator: (type_identifier) @name) @definition.typedef
+(alias_declaration name: (type_identifier) @name) @definition.typedef
+
+(preproc_function_def name: (identifier) @name) @definition.macro
+(preproc_def name: (identifier) @name) @definition.macro
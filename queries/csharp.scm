@@ -0,0 +1,16 @@
+;; Namespaces
+(namespace_declaration name: (_) @namespace)
+(file_scoped_namespace_declaration name: (_) @namespace)
+
+;; Types
+(class_declaration) @declaration
+(struct_declaration) @declaration
+(interface_declaration) @declaration
+(record_declaration) @declaration
+(enum_declaration) @declaration
+(delegate_declaration) @declaration
+
+;; Members
+(method_declaration) @declaration
+(constructor_declaration) @declaration
+(property_declaration) @property
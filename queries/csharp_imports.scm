@@ -0,0 +1,3 @@
+;; using System.Text;
+(using_directive
+  [(qualified_name) (identifier)] @import_source)
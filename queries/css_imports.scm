@@ -0,0 +1,9 @@
+;; @import "file.css"
+(import_statement
+  (string_value) @import_source)
+
+;; @import url(file.css)
+(import_statement
+  (call_expression
+    (arguments
+      [(string_value) (plain_value)] @import_source)))
@@ -0,0 +1,24 @@
+;; Symbol definitions shared by JavaScript and TypeScript. Each pattern pairs
+;; a @name with a @definition.<kind>; when several match one name, the first
+;; pattern wins.
+
+(function_declaration name: (identifier) @name) @definition.function
+(generator_function_declaration name: (identifier) @name) @definition.function
+(class_declaration name: (_) @name) @definition.class
+(method_definition name: (_) @name) @definition.method
+
+;; const handler = () => {}, const Widget = class {}
+(variable_declarator
+  name: (identifier) @name
+  value: [(arrow_function) (function_expression) (generator_function)]) @definition.function
+(variable_declarator
+  name: (identifier) @name
+  value: (class)) @definition.class
+
+;; Module-level bindings
+(program
+  (lexical_declaration
+    (variable_declarator name: (identifier) @name) @definition.variable))
+(export_statement
+  declaration: (lexical_declaration
+    (variable_declarator name: (identifier) @name) @definition.variable))
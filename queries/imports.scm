@@ -0,0 +1,18 @@
+;; ES module imports
+(import_statement
+  source: (string) @import_source)
+
+;; Re-exports (export ... from)
+(export_statement
+  source: (string) @import_source)
+
+;; CommonJS require
+(call_expression
+  function: (identifier) @require_fn
+  arguments: (arguments . (string) @import_source)
+  (#eq? @require_fn "require"))
+
+;; Dynamic import()
+(call_expression
+  function: (import)
+  arguments: (arguments . (string) @import_source))
@@ -0,0 +1,15 @@
+;; Package
+(package_declaration
+  [(scoped_identifier) (identifier)] @package)
+
+;; Types
+(class_declaration) @declaration
+(interface_declaration) @declaration
+(record_declaration) @declaration
+(enum_declaration) @declaration
+(annotation_type_declaration) @declaration
+
+;; Members
+(method_declaration) @declaration
+(constructor_declaration) @declaration
+(annotation_type_element_declaration) @declaration
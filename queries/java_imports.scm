@@ -0,0 +1,3 @@
+;; import com.acme.Foo; / import com.acme.*;
+(import_declaration
+  [(scoped_identifier) (identifier)] @import_source)
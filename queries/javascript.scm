@@ -1,3 +1,5 @@
+;; JavaScript signatures (also the base for --queries-dir/javascript.scm)
+
 ;; Functions
 (function_declaration
   name: (identifier) @fname
@@ -8,21 +10,108 @@
   name: (property_identifier) @mname
   parameters: (formal_parameters) @mparams)
 
-;; Classes
+;; Classes (JS names are identifiers, TS names are type_identifiers)
 (class_declaration
-  name: (identifier) @cname)
+  name: (_) @cname)
 
-;; Variable assigned arrow function
+;; Variable assigned arrow function: const f = (a) => {}, const f = a => {}
+(lexical_declaration
+  (variable_declarator
+    name: (identifier) @vname
+    value: (arrow_function
+      parameters: (formal_parameters) @vparams) @is_arrow)) @vdecl
 (lexical_declaration
   (variable_declarator
     name: (identifier) @vname
     value: (arrow_function
-      parameters: (formal_parameters) @vparams) @is_arrow))
+      parameter: (identifier) @vparams) @is_arrow)) @vdecl
 
 ;; Variable assigned function expression
 (lexical_declaration
   (variable_declarator
     name: (identifier) @vname
-    value: (function
-      (formal_parameters) @vparams)))
+    value: (function_expression
+      (formal_parameters) @vparams))) @vdecl
+
+;; var-declared arrow function
+(variable_declaration
+  (variable_declarator
+    name: (identifier) @vname
+    value: (arrow_function
+      parameters: (formal_parameters) @vparams) @is_arrow)) @vdecl
+(variable_declaration
+  (variable_declarator
+    name: (identifier) @vname
+    value: (arrow_function
+      parameter: (identifier) @vparams) @is_arrow)) @vdecl
+
+;; var-declared function expression
+(variable_declaration
+  (variable_declarator
+    name: (identifier) @vname
+    value: (function_expression
+      (formal_parameters) @vparams))) @vdecl
+
+;; Anonymous default export: export default function () {}
+(export_statement
+  value: (function_expression
+    (formal_parameters) @dparams))
+
+;; Anonymous default export: export default () => {}
+(export_statement
+  value: (arrow_function
+    parameters: (formal_parameters) @dparams) @is_arrow)
+(export_statement
+  value: (arrow_function
+    parameter: (identifier) @dparams) @is_arrow)
+
+;; CommonJS: module.exports = function () {}, exports.foo = function foo() {}
+(expression_statement
+  (assignment_expression
+    left: (member_expression) @aname
+    right: (function_expression
+      (formal_parameters) @aparams)))
+
+;; CommonJS: module.exports = () => {}, exports.foo = x => {}
+(expression_statement
+  (assignment_expression
+    left: (member_expression) @aname
+    right: (arrow_function
+      parameters: (formal_parameters) @aparams) @is_arrow))
+(expression_statement
+  (assignment_expression
+    left: (member_expression) @aname
+    right: (arrow_function
+      parameter: (identifier) @aparams) @is_arrow))
+
+;; Object literal function properties: { get: function () {} }
+(pair
+  key: (property_identifier) @mname
+  value: (function_expression
+    (formal_parameters) @mparams))
+
+;; Object literal arrow properties: { get: () => {} }
+(pair
+  key: (property_identifier) @mname
+  value: (arrow_function
+    parameters: (formal_parameters) @mparams))
+(pair
+  key: (property_identifier) @mname
+  value: (arrow_function
+    parameter: (identifier) @mparams))
+
+;; Class property arrow function: handleClick = () => {}, handle = e => {}
+(field_definition
+  property: (property_identifier) @pname
+  value: (arrow_function
+    parameters: (formal_parameters) @pparams) @is_arrow)
+(field_definition
+  property: (property_identifier) @pname
+  value: (arrow_function
+    parameter: (identifier) @pparams) @is_arrow)
 
+;; Class property function expression: handleClick = function () {}
+(field_definition
+  property: (property_identifier) @pname
+  value: (function_expression
+    (formal_parameters) @pparams))
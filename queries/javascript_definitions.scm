@@ -0,0 +1,5 @@
+;; Class fields
+(field_definition
+  property: (_) @name
+  value: [(arrow_function) (function_expression)]) @definition.method
+(field_definition property: (_) @name) @definition.field
@@ -0,0 +1,13 @@
+;; Package
+(package_header
+  (identifier) @package)
+
+;; Classes, interfaces, data/enum/sealed classes
+(class_declaration) @declaration
+
+;; Objects
+(object_declaration) @declaration
+(companion_object) @declaration
+
+;; Functions, including extension and suspend functions
+(function_declaration) @declaration
@@ -0,0 +1,13 @@
+;; Kotlin declarations have no `name` field; the name is a direct child
+(class_declaration (type_identifier) @name) @definition.class
+(object_declaration (type_identifier) @name) @definition.object
+(function_declaration (simple_identifier) @name) @definition.function
+(type_alias (type_identifier) @name) @definition.typealias
+
+;; Top-level and member properties, not locals
+(source_file
+  (property_declaration
+    (variable_declaration (simple_identifier) @name)) @definition.property)
+(class_body
+  (property_declaration
+    (variable_declaration (simple_identifier) @name)) @definition.property)
@@ -0,0 +1,3 @@
+;; import com.acme.Foo
+(import_header
+  (identifier) @import_source)
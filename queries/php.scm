@@ -0,0 +1,14 @@
+;; Namespaces
+(namespace_definition name: (_) @namespace)
+
+;; Types
+(class_declaration) @declaration
+(interface_declaration) @declaration
+(trait_declaration) @declaration
+(enum_declaration) @declaration
+
+;; Functions and members
+(function_definition) @declaration
+(method_declaration) @declaration
+(property_declaration) @declaration
+(use_declaration) @declaration
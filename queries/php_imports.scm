@@ -0,0 +1,9 @@
+;; use App\Models\User;
+(namespace_use_clause
+  [(qualified_name) (name)] @import_source)
+
+;; require 'config.php'; include_once "helpers.php";
+(require_expression (string) @import_source)
+(require_once_expression (string) @import_source)
+(include_expression (string) @import_source)
+(include_once_expression (string) @import_source)
@@ -0,0 +1,12 @@
+;; Modules and classes
+(module) @module
+(class) @class
+
+;; Methods, including `def self.find`
+(method) @method
+(singleton_method) @method
+
+;; attr_accessor :name, :email
+(call
+  method: (identifier) @attr
+  (#match? @attr "^attr_(accessor|reader|writer)$")) @attribute
@@ -0,0 +1,9 @@
+;; `class Admin::User` is named by its last segment
+(class
+  name: [(constant) @name (scope_resolution name: (_) @name)]) @definition.class
+(module
+  name: [(constant) @name (scope_resolution name: (_) @name)]) @definition.module
+
+(method name: (_) @name) @definition.method
+(singleton_method name: (_) @name) @definition.method
+(assignment left: (constant) @name) @definition.constant
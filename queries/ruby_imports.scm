@@ -0,0 +1,5 @@
+;; require 'json' / require_relative 'base'
+(call
+  method: (identifier) @require
+  arguments: (argument_list . (string (string_content) @import_source))
+  (#match? @require "^require(_relative)?$"))
@@ -1,3 +1,4 @@
+use crate::model::ErrorKind;
 use std::fmt;
 
 /// Unified error type for the entire application
@@ -5,9 +6,30 @@ use std::fmt;
 pub enum TalosError {
     Io(std::io::Error),
     Serialization(serde_json::Error),
-    TreeSitter(tree_sitter::QueryError),
+    /// Invalid tree-sitter query; `path` is set for user-supplied query files
+    TreeSitter {
+        error: tree_sitter::QueryError,
+        path: Option<std::path::PathBuf>,
+    },
     InvalidInput(String),
     ScanError(String),
+    /// The summary could not be encoded in the requested output format
+    Encode {
+        format: &'static str,
+        message: String,
+    },
+    /// An external extractor failed: `Io` when it could not be started, run or
+    /// did not answer in time, `Parse` when it answered with an error or garbage
+    Plugin {
+        name: String,
+        message: String,
+        kind: ErrorKind,
+    },
+    /// Malformed TOML or YAML config, such as `Cargo.toml` or `pnpm-workspace.yaml`
+    Config {
+        message: String,
+        position: Option<(usize, usize)>,
+    },
 }
 
 impl fmt::Display for TalosError {
@@ -15,9 +37,57 @@ impl fmt::Display for TalosError {
         match self {
             TalosError::Io(err) => write!(f, "IO error: {}", err),
             TalosError::Serialization(err) => write!(f, "Serialization error: {}", err),
-            TalosError::TreeSitter(err) => write!(f, "Tree-sitter query error: {}", err),
+            TalosError::TreeSitter { error, path: None } => {
+                write!(f, "Tree-sitter query error: {}", error)
+            }
+            TalosError::TreeSitter {
+                error,
+                path: Some(path),
+            } => write!(
+                f,
+                "Tree-sitter query error in {}: {}",
+                path.display(),
+                error
+            ),
             TalosError::InvalidInput(msg) => write!(f, "Invalid input: {}", msg),
             TalosError::ScanError(msg) => write!(f, "Scan error: {}", msg),
+            TalosError::Encode { format, message } => {
+                write!(f, "Failed to encode {}: {}", format, message)
+            }
+            TalosError::Plugin { name, message, .. } => {
+                write!(f, "Plugin '{}': {}", name, message)
+            }
+            TalosError::Config { message, .. } => write!(f, "Config error: {}", message),
+        }
+    }
+}
+
+impl TalosError {
+    /// Coarse cause, used to classify per-file errors in the output
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            TalosError::Io(err) => match err.kind() {
+                std::io::ErrorKind::PermissionDenied => ErrorKind::Permission,
+                std::io::ErrorKind::InvalidData => ErrorKind::Encoding,
+                _ => ErrorKind::Io,
+            },
+            TalosError::Serialization(_) => ErrorKind::Parse,
+            TalosError::TreeSitter { .. } => ErrorKind::Query,
+            TalosError::InvalidInput(_) => ErrorKind::Io,
+            TalosError::ScanError(_) => ErrorKind::Parse,
+            TalosError::Encode { .. } => ErrorKind::Io,
+            TalosError::Plugin { kind, .. } => *kind,
+            TalosError::Config { .. } => ErrorKind::Parse,
+        }
+    }
+
+    /// 1-based (line, column) of the error within its file, when known
+    pub fn position(&self) -> Option<(usize, usize)> {
+        match self {
+            TalosError::Serialization(err) if err.line() > 0 => Some((err.line(), err.column())),
+            TalosError::TreeSitter { error, .. } => Some((error.row + 1, error.column + 1)),
+            TalosError::Config { position, .. } => *position,
+            _ => None,
         }
     }
 }
@@ -36,9 +106,22 @@ impl From<serde_json::Error> for TalosError {
     }
 }
 
+impl From<serde_yaml::Error> for TalosError {
+    fn from(err: serde_yaml::Error) -> Self {
+        let position = err.location().map(|loc| (loc.line(), loc.column()));
+        TalosError::Config {
+            message: err.to_string(),
+            position,
+        }
+    }
+}
+
 impl From<tree_sitter::QueryError> for TalosError {
     fn from(err: tree_sitter::QueryError) -> Self {
-        TalosError::TreeSitter(err)
+        TalosError::TreeSitter {
+            error: err,
+            path: None,
+        }
     }
 }
 
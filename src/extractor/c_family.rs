@@ -0,0 +1,520 @@
+use super::{
+    collect_definitions, collect_imports, custom_signature, declaration_header, parse_diagnostics,
+    Extraction, SupportedLang,
+};
+use crate::error::{TalosError, TalosResult};
+use std::collections::BTreeSet;
+use tree_sitter::{Language, Node, Parser, Query, QueryCapture, QueryCursor};
+
+const C_QUERY: &str = include_str!("../../queries/c.scm");
+const CPP_QUERY: &str = include_str!("../../queries/cpp.scm");
+const C_IMPORTS_QUERY: &str = include_str!("../../queries/c_imports.scm");
+const C_DEFINITIONS_QUERY: &str = include_str!("../../queries/c_definitions.scm");
+const CPP_DEFINITIONS_QUERY: &str = include_str!("../../queries/cpp_definitions.scm");
+
+/// Declarations nested in these are rendered as part of their parent (or not at all)
+const MEMBER_CONTAINERS: &[&str] = &["field_declaration_list", "compound_statement"];
+
+/// Declarator wrappers between a declaration and its `function_declarator`
+const DECLARATOR_WRAPPERS: &[&str] = &[
+    "pointer_declarator",
+    "reference_declarator",
+    "attributed_declarator",
+];
+
+pub fn extract_c(code: &str, query: Option<&str>) -> TalosResult<Extraction> {
+    extract_with_query(
+        code,
+        SupportedLang::C.grammar(),
+        query.unwrap_or(C_QUERY),
+        C_DEFINITIONS_QUERY,
+    )
+}
+
+pub fn extract_cpp(code: &str, query: Option<&str>) -> TalosResult<Extraction> {
+    extract_with_query(
+        code,
+        SupportedLang::Cpp.grammar(),
+        query.unwrap_or(CPP_QUERY),
+        CPP_DEFINITIONS_QUERY,
+    )
+}
+
+fn extract_with_query(
+    code: &str,
+    lang: Language,
+    query_str: &str,
+    definitions_query_str: &str,
+) -> TalosResult<Extraction> {
+    let mut parser = Parser::new();
+    parser
+        .set_language(lang)
+        .map_err(|e| TalosError::ScanError(format!("Failed to set parser language: {}", e)))?;
+
+    let tree = parser
+        .parse(code, None)
+        .ok_or_else(|| TalosError::ScanError("Failed to parse code".to_string()))?;
+
+    let query = Query::new(lang, query_str)?;
+    let mut cursor = QueryCursor::new();
+    let mut signatures: BTreeSet<String> = BTreeSet::new();
+
+    for query_match in cursor.matches(&query, tree.root_node(), code.as_bytes()) {
+        if let Some(signature) = create_c_signature(&query, query_match.captures, code) {
+            signatures.insert(signature);
+        }
+    }
+
+    let imports_query = Query::new(lang, C_IMPORTS_QUERY)?;
+    let imports = collect_imports(&imports_query, tree.root_node(), code);
+    let definitions_query = Query::new(lang, definitions_query_str)?;
+    let definitions = collect_definitions(&definitions_query, tree.root_node(), code);
+
+    Ok(Extraction {
+        signatures: signatures.into_iter().collect(),
+        imports,
+        components: Vec::new(),
+        definitions,
+        diagnostics: parse_diagnostics(tree.root_node(), code),
+        encoding: None,
+    })
+}
+
+#[derive(Debug)]
+enum CSignatureType {
+    Macro(String, String),
+    Namespace(String),
+    Declaration(String),
+}
+
+impl CSignatureType {
+    fn render(self) -> String {
+        match self {
+            Self::Macro(name, params) => format!("#define {}{}", name, params),
+            Self::Namespace(name) => format!("namespace {}", name),
+            Self::Declaration(text) => text,
+        }
+    }
+}
+
+struct CCaptureMap<'a> {
+    query: &'a Query,
+    captures: &'a [QueryCapture<'a>],
+}
+
+impl<'a> CCaptureMap<'a> {
+    fn new(query: &'a Query, captures: &'a [QueryCapture<'a>]) -> Self {
+        Self { query, captures }
+    }
+
+    fn get_node(&self, name: &str) -> Option<Node<'a>> {
+        self.captures
+            .iter()
+            .find(|c| self.query.capture_names()[c.index as usize] == name)
+            .map(|c| c.node)
+    }
+}
+
+fn create_c_signature(query: &Query, captures: &[QueryCapture], code: &str) -> Option<String> {
+    let capture_map = CCaptureMap::new(query, captures);
+    let Some(signature_type) = detect_c_signature_type(&capture_map, code) else {
+        return custom_signature(query, captures, code);
+    };
+    Some(signature_type.render())
+}
+
+fn detect_c_signature_type(captures: &CCaptureMap, code: &str) -> Option<CSignatureType> {
+    // #define NAME(a, b)
+    if let Some(node) = captures.get_node("macro") {
+        let name = text(node.child_by_field_name("name")?, code);
+        let params = node
+            .child_by_field_name("parameters")
+            .map(|p| collapse(text(p, code)))
+            .unwrap_or_default();
+        return Some(CSignatureType::Macro(name.to_string(), params));
+    }
+
+    // namespace gfx
+    if let Some(node) = captures.get_node("namespace") {
+        return Some(CSignatureType::Namespace(text(node, code).to_string()));
+    }
+
+    // Prototypes and definitions, e.g. `int *alloc(size_t n)`
+    if let Some(node) = captures.get_node("function") {
+        if is_member(node) || !declares_function(node) {
+            return None;
+        }
+        let header = declaration_header(node, code, &[]);
+        return Some(CSignatureType::Declaration(with_template(
+            node, header, code,
+        )));
+    }
+
+    // struct point { int x; int y }, class Widget : public Base { public: ... }
+    if let Some(node) = captures.get_node("record") {
+        let parent_kind = node.parent().map(|p| p.kind());
+        if is_member(node) || parent_kind == Some("type_definition") {
+            return None;
+        }
+        let summary = render_record(node, code)?;
+        return Some(CSignatureType::Declaration(with_template(
+            node, summary, code,
+        )));
+    }
+
+    // typedef struct { ... } point_t, typedef int (*handler_t)(int), using id = int
+    if let Some(node) = captures.get_node("typedef") {
+        if is_member(node) {
+            return None;
+        }
+        return Some(CSignatureType::Declaration(with_template(
+            node,
+            render_typedef(node, code)?,
+            code,
+        )));
+    }
+
+    None
+}
+
+/// True when the node sits inside a class body or function body
+fn is_member(node: Node) -> bool {
+    let mut current = node.parent();
+    while let Some(parent) = current {
+        if MEMBER_CONTAINERS.contains(&parent.kind()) {
+            return true;
+        }
+        current = parent.parent();
+    }
+    false
+}
+
+/// True for function definitions and prototypes; false for variables, including
+/// function pointers like `int (*fp)(int)`
+fn declares_function(node: Node) -> bool {
+    let mut declarator = node.child_by_field_name("declarator");
+    while let Some(current) = declarator {
+        match current.kind() {
+            "function_declarator" => {
+                return current
+                    .child_by_field_name("declarator")
+                    .is_some_and(|inner| inner.kind() != "parenthesized_declarator");
+            }
+            kind if DECLARATOR_WRAPPERS.contains(&kind) => {
+                declarator = current
+                    .child_by_field_name("declarator")
+                    .or_else(|| current.named_child(current.named_child_count().saturating_sub(1)));
+            }
+            _ => return false,
+        }
+    }
+    false
+}
+
+/// Prefix `template <typename T>` when the node is the body of a template declaration
+fn with_template(node: Node, rendered: String, code: &str) -> String {
+    let Some(params) = node
+        .parent()
+        .filter(|p| p.kind() == "template_declaration")
+        .and_then(|p| p.child_by_field_name("parameters"))
+    else {
+        return rendered;
+    };
+    format!("template {} {}", collapse(text(params, code)), rendered)
+}
+
+/// One-line summary of a struct, union, enum or class with its members
+fn render_record(node: Node, code: &str) -> Option<String> {
+    let body = node.child_by_field_name("body")?;
+    let head = collapse(code.get(node.start_byte()..body.start_byte())?);
+
+    let members = if body.kind() == "enumerator_list" {
+        let mut cursor = body.walk();
+        let enumerators: Vec<String> = body
+            .named_children(&mut cursor)
+            .filter(|c| c.kind() == "enumerator")
+            .map(|c| collapse(text(c, code)))
+            .collect();
+        enumerators.join(", ")
+    } else {
+        render_members(node, body, code)
+    };
+
+    if members.is_empty() {
+        Some(format!("{} {{}}", head))
+    } else {
+        Some(format!("{} {{ {} }}", head, members))
+    }
+}
+
+/// Members in source order, grouped under their access labels. Classes open
+/// with an implicit `private:` section; structs and unions are public by default.
+fn render_members(record: Node, body: Node, code: &str) -> String {
+    let mut parts: Vec<String> = Vec::new();
+    let mut labelled = record.kind() != "class_specifier";
+    let mut cursor = body.walk();
+
+    for member in body.named_children(&mut cursor) {
+        if member.kind() == "access_specifier" {
+            parts.push(format!("{}:", text(member, code).trim_end_matches(':')));
+            labelled = true;
+            continue;
+        }
+        let Some(rendered) = render_member(member, code) else {
+            continue;
+        };
+        if !labelled {
+            parts.push("private:".to_string());
+            labelled = true;
+        }
+        parts.push(format!("{};", rendered));
+    }
+
+    parts.join(" ")
+}
+
+fn render_member(member: Node, code: &str) -> Option<String> {
+    match member.kind() {
+        "comment" => None,
+        kind if kind.starts_with("preproc_") => None,
+        "function_definition" => Some(declaration_header(member, code, &[])),
+        "template_declaration" => {
+            let params = member.child_by_field_name("parameters")?;
+            let mut cursor = member.walk();
+            let inner = member
+                .named_children(&mut cursor)
+                .filter(|c| c.id() != params.id())
+                .last()?;
+            Some(format!(
+                "template {} {}",
+                collapse(text(params, code)),
+                render_member(inner, code)?
+            ))
+        }
+        _ => {
+            let rendered = collapse(text(member, code));
+            let rendered = rendered.trim_end_matches([';', ' ']).to_string();
+            (!rendered.is_empty()).then_some(rendered)
+        }
+    }
+}
+
+/// Anonymous records in typedefs are expanded: `typedef struct { int x; } point_t`
+fn render_typedef(node: Node, code: &str) -> Option<String> {
+    let ty = node.child_by_field_name("type");
+    let record = ty.filter(|t| t.child_by_field_name("body").is_some());
+
+    let Some(record) = record else {
+        let rendered = collapse(text(node, code));
+        return Some(rendered.trim_end_matches([';', ' ']).to_string());
+    };
+
+    let mut cursor = node.walk();
+    let declarators: Vec<String> = node
+        .children_by_field_name("declarator", &mut cursor)
+        .map(|d| collapse(text(d, code)))
+        .collect();
+    Some(format!(
+        "typedef {} {}",
+        render_record(record, code)?,
+        declarators.join(", ")
+    ))
+}
+
+/// What a rendered C/C++ signature declares, for matching sources against headers
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CSymbol {
+    Macro(String),
+    Namespace(String),
+    /// A struct, union, enum, class or typedef
+    Type(String),
+    /// A free function, by its unqualified name
+    Function(String),
+    /// A qualified definition such as `void Widget::draw()` or `void gfx::init()`,
+    /// by the last qualifier and the unqualified name
+    Qualified {
+        owner: String,
+        name: String,
+    },
+}
+
+/// The symbol `signature` (as rendered by this extractor) declares, or `None`
+/// when it cannot be told, e.g. for operators
+pub fn declared_symbol(signature: &str) -> Option<CSymbol> {
+    let signature = strip_template(signature.trim());
+    if let Some(rest) = signature.strip_prefix("#define ") {
+        return leading_identifier(rest).map(CSymbol::Macro);
+    }
+    if let Some(rest) = signature.strip_prefix("namespace ") {
+        return Some(CSymbol::Namespace(rest.trim().to_string()));
+    }
+    if let Some(rest) = signature.strip_prefix("using ") {
+        return leading_identifier(rest).map(CSymbol::Type);
+    }
+    if signature.starts_with("typedef ") {
+        // The name follows the last record body: `typedef struct { ... } point_t`
+        let tail = &signature[signature.rfind('}').map_or(0, |i| i + 1)..];
+        if let Some(start) = tail.find("(*") {
+            return leading_identifier(&tail[start + 2..]).map(CSymbol::Type);
+        }
+        return tail
+            .rsplit(|c: char| !is_identifier_char(c))
+            .find(|word| !word.is_empty())
+            .map(|word| CSymbol::Type(word.to_string()));
+    }
+
+    let paren = signature.find('(');
+    let brace = signature.find('{');
+    if let Some(brace) = brace.filter(|brace| paren.is_none_or(|paren| *brace < paren)) {
+        let head = &signature[..brace];
+        for keyword in [
+            "enum class ",
+            "enum struct ",
+            "struct ",
+            "class ",
+            "union ",
+            "enum ",
+        ] {
+            if let Some(rest) = head.strip_prefix(keyword) {
+                return leading_identifier(rest).map(CSymbol::Type);
+            }
+        }
+        return None;
+    }
+
+    let head = signature[..paren?].trim_end();
+    let name = head
+        .rsplit(|c: char| !(is_identifier_char(c) || c == ':' || c == '~'))
+        .next()
+        .filter(|name| !name.is_empty())?;
+    match name.rsplit_once("::") {
+        Some((owner, name)) => Some(CSymbol::Qualified {
+            owner: owner.rsplit("::").next().unwrap_or(owner).to_string(),
+            name: name.to_string(),
+        }),
+        None => Some(CSymbol::Function(name.to_string())),
+    }
+}
+
+/// `template <typename T> T max(T a, T b)` without the template parameters
+fn strip_template(signature: &str) -> &str {
+    let Some(rest) = signature.strip_prefix("template ") else {
+        return signature;
+    };
+    let mut depth = 0;
+    for (idx, c) in rest.char_indices() {
+        match c {
+            '<' => depth += 1,
+            '>' => {
+                depth -= 1;
+                if depth == 0 {
+                    return rest[idx + 1..].trim_start();
+                }
+            }
+            _ => {}
+        }
+    }
+    signature
+}
+
+fn leading_identifier(text: &str) -> Option<String> {
+    let text = text.trim_start();
+    let end = text
+        .find(|c: char| !is_identifier_char(c))
+        .unwrap_or(text.len());
+    (end > 0).then(|| text[..end].to_string())
+}
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+fn collapse(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn text<'a>(node: Node, code: &'a str) -> &'a str {
+    code.get(node.byte_range()).unwrap_or_default().trim()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn symbols(code: &str) -> Vec<Option<CSymbol>> {
+        extract_cpp(code, None)
+            .unwrap()
+            .signatures
+            .iter()
+            .map(|sig| declared_symbol(sig))
+            .collect()
+    }
+
+    fn function(name: &str) -> Option<CSymbol> {
+        Some(CSymbol::Function(name.to_string()))
+    }
+
+    #[test]
+    fn prototype_and_definition_declare_the_same_function() {
+        assert_eq!(symbols("int f(int);"), vec![function("f")]);
+        assert_eq!(symbols("int f(int x) { return x; }"), vec![function("f")]);
+        assert_eq!(
+            symbols("extern char *dup(const char *s);"),
+            vec![function("dup")]
+        );
+    }
+
+    #[test]
+    fn types_macros_and_namespaces() {
+        let ty = |name: &str| Some(CSymbol::Type(name.to_string()));
+        assert_eq!(
+            declared_symbol("struct point { int x; int y; }"),
+            ty("point")
+        );
+        assert_eq!(
+            declared_symbol("class Widget : public Base { public: void draw(); }"),
+            ty("Widget")
+        );
+        assert_eq!(declared_symbol("enum class Color { Red }"), ty("Color"));
+        assert_eq!(
+            declared_symbol("typedef struct { int x; } point_t"),
+            ty("point_t")
+        );
+        assert_eq!(
+            declared_symbol("typedef int (*handler_t)(int)"),
+            ty("handler_t")
+        );
+        assert_eq!(declared_symbol("using id = int"), ty("id"));
+        assert_eq!(
+            declared_symbol("#define MAX(a, b)"),
+            Some(CSymbol::Macro("MAX".to_string()))
+        );
+        assert_eq!(
+            declared_symbol("namespace gfx"),
+            Some(CSymbol::Namespace("gfx".to_string()))
+        );
+    }
+
+    #[test]
+    fn templates_and_qualified_names() {
+        assert_eq!(
+            declared_symbol("template <typename T, typename U = std::vector<T>> T max(T a, U b)"),
+            function("max")
+        );
+        assert_eq!(
+            declared_symbol("struct point *make_point(int x)"),
+            function("make_point")
+        );
+        assert_eq!(
+            declared_symbol("void gfx::Widget::draw() const"),
+            Some(CSymbol::Qualified {
+                owner: "Widget".to_string(),
+                name: "draw".to_string(),
+            })
+        );
+        assert_eq!(
+            declared_symbol("bool operator==(const A &a, const A &b)"),
+            None
+        );
+    }
+}
@@ -0,0 +1,169 @@
+use super::{
+    attributed_header, collect_definitions, collect_imports, custom_signature, parse_diagnostics,
+    Extraction, SupportedLang,
+};
+use crate::error::{TalosError, TalosResult};
+use std::collections::BTreeSet;
+use tree_sitter::{Node, Parser, Query, QueryCapture, QueryCursor};
+
+const CSHARP_QUERY: &str = include_str!("../../queries/csharp.scm");
+const CSHARP_IMPORTS_QUERY: &str = include_str!("../../queries/csharp_imports.scm");
+const CSHARP_DEFINITIONS_QUERY: &str = include_str!("../../queries/csharp_definitions.scm");
+
+/// Bodies that end a header besides the `body` field: property accessors and `=> expr`
+const CSHARP_BODIES: &[&str] = &["accessor_list", "arrow_expression_clause"];
+
+pub fn extract_csharp(code: &str, query: Option<&str>) -> TalosResult<Extraction> {
+    let lang = SupportedLang::CSharp.grammar();
+    let mut parser = Parser::new();
+    parser
+        .set_language(lang)
+        .map_err(|e| TalosError::ScanError(format!("Failed to set parser language: {}", e)))?;
+
+    let tree = parser
+        .parse(code, None)
+        .ok_or_else(|| TalosError::ScanError("Failed to parse code".to_string()))?;
+
+    let query = Query::new(lang, query.unwrap_or(CSHARP_QUERY))?;
+    let mut cursor = QueryCursor::new();
+    let mut signatures: BTreeSet<String> = BTreeSet::new();
+
+    for query_match in cursor.matches(&query, tree.root_node(), code.as_bytes()) {
+        if let Some(signature) = create_csharp_signature(&query, query_match.captures, code) {
+            signatures.insert(signature);
+        }
+    }
+
+    let imports_query = Query::new(lang, CSHARP_IMPORTS_QUERY)?;
+    let imports = collect_imports(&imports_query, tree.root_node(), code);
+    let definitions_query = Query::new(lang, CSHARP_DEFINITIONS_QUERY)?;
+    let definitions = collect_definitions(&definitions_query, tree.root_node(), code);
+
+    Ok(Extraction {
+        signatures: signatures.into_iter().collect(),
+        imports,
+        components: Vec::new(),
+        definitions,
+        diagnostics: parse_diagnostics(tree.root_node(), code),
+        encoding: None,
+    })
+}
+
+#[derive(Debug)]
+enum CSharpSignatureType {
+    Namespace(String),
+    Property(String, Vec<String>),
+    Declaration(String),
+}
+
+impl CSharpSignatureType {
+    fn render(self) -> String {
+        match self {
+            Self::Namespace(name) => format!("namespace {}", name),
+            Self::Property(header, accessors) if accessors.is_empty() => header,
+            Self::Property(header, accessors) => {
+                format!("{} {{ {}; }}", header, accessors.join("; "))
+            }
+            Self::Declaration(header) => header,
+        }
+    }
+}
+
+struct CSharpCaptureMap<'a> {
+    query: &'a Query,
+    captures: &'a [QueryCapture<'a>],
+}
+
+impl<'a> CSharpCaptureMap<'a> {
+    fn new(query: &'a Query, captures: &'a [QueryCapture<'a>]) -> Self {
+        Self { query, captures }
+    }
+
+    fn get_node(&self, name: &str) -> Option<Node<'a>> {
+        self.captures
+            .iter()
+            .find(|c| self.query.capture_names()[c.index as usize] == name)
+            .map(|c| c.node)
+    }
+}
+
+fn create_csharp_signature(query: &Query, captures: &[QueryCapture], code: &str) -> Option<String> {
+    let capture_map = CSharpCaptureMap::new(query, captures);
+    let Some(signature_type) = detect_csharp_signature_type(&capture_map, code) else {
+        return custom_signature(query, captures, code);
+    };
+    Some(signature_type.render())
+}
+
+fn detect_csharp_signature_type(
+    captures: &CSharpCaptureMap,
+    code: &str,
+) -> Option<CSharpSignatureType> {
+    // Namespace, block or file-scoped
+    if let Some(node) = captures.get_node("namespace") {
+        let name = code.get(node.byte_range()).unwrap_or_default().trim();
+        return Some(CSharpSignatureType::Namespace(name.to_string()));
+    }
+
+    // `[Inject] public ILogger Logger { get; private set; }`
+    if let Some(node) = captures.get_node("property") {
+        let header = attributed_header(node, code, CSHARP_BODIES);
+        let accessors = node
+            .child_by_field_name("accessors")
+            .map(|list| {
+                let mut cursor = list.walk();
+                let names: Vec<String> = list
+                    .named_children(&mut cursor)
+                    .filter(|a| a.kind() == "accessor_declaration")
+                    .map(|a| attributed_header(a, code, CSHARP_BODIES))
+                    .collect();
+                names
+            })
+            .unwrap_or_default();
+        return Some(CSharpSignatureType::Property(header, accessors));
+    }
+
+    // Types and members, attributes included
+    if let Some(node) = captures.get_node("declaration") {
+        let header = attributed_header(node, code, CSHARP_BODIES);
+        if !header.is_empty() {
+            return Some(CSharpSignatureType::Declaration(header));
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn attributed_members_and_usings() {
+        let code = r#"
+using System.Text;
+namespace Shop.Models {
+  [Serializable]
+  public class Order {
+    [JsonProperty("id")]
+    public int Id { get; private set; }
+    public async Task<bool> Save(int retries) { return true; }
+  }
+}
+"#;
+        let extraction = extract_csharp(code, None).unwrap();
+        for expected in [
+            "namespace Shop.Models",
+            "[Serializable] public class Order",
+            r#"[JsonProperty("id")] public int Id { get; private set; }"#,
+            "public async Task<bool> Save(int retries)",
+        ] {
+            assert!(
+                extraction.signatures.contains(&expected.to_string()),
+                "missing {expected:?} in {:?}",
+                extraction.signatures
+            );
+        }
+        assert_eq!(extraction.imports, vec!["System.Text"]);
+    }
+}
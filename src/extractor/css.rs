@@ -1,18 +1,20 @@
+use super::{collect_imports, custom_signature, parse_diagnostics, Extraction, SupportedLang};
 use crate::error::{TalosError, TalosResult};
 use std::collections::BTreeSet;
 use tree_sitter::{Language, Node, Parser, Query, QueryCapture, QueryCursor};
 
-fn lang_css() -> Language {
-    tree_sitter_css::language()
-}
-
 const CSS_QUERY: &str = include_str!("../../queries/css.scm");
-
-pub fn extract_css(code: &str) -> TalosResult<Vec<String>> {
-    extract_with_query(code, lang_css(), CSS_QUERY)
+const CSS_IMPORTS_QUERY: &str = include_str!("../../queries/css_imports.scm");
+
+pub fn extract_css(code: &str, query: Option<&str>) -> TalosResult<Extraction> {
+    extract_with_query(
+        code,
+        SupportedLang::CSS.grammar(),
+        query.unwrap_or(CSS_QUERY),
+    )
 }
 
-fn extract_with_query(code: &str, lang: Language, query_str: &str) -> TalosResult<Vec<String>> {
+fn extract_with_query(code: &str, lang: Language, query_str: &str) -> TalosResult<Extraction> {
     let mut parser = Parser::new();
     parser
         .set_language(lang)
@@ -32,7 +34,17 @@ fn extract_with_query(code: &str, lang: Language, query_str: &str) -> TalosResul
         }
     }
 
-    Ok(signatures.into_iter().collect())
+    let imports_query = Query::new(lang, CSS_IMPORTS_QUERY)?;
+    let imports = collect_imports(&imports_query, tree.root_node(), code);
+
+    Ok(Extraction {
+        signatures: signatures.into_iter().collect(),
+        imports,
+        components: Vec::new(),
+        definitions: Vec::new(),
+        diagnostics: parse_diagnostics(tree.root_node(), code),
+        encoding: None,
+    })
 }
 
 #[derive(Debug)]
@@ -78,7 +90,9 @@ impl<'a> CssCaptureMap<'a> {
 
 fn create_css_signature(query: &Query, captures: &[QueryCapture], code: &str) -> Option<String> {
     let capture_map = CssCaptureMap::new(query, captures);
-    let signature_type = detect_css_signature_type(&capture_map, code)?;
+    let Some(signature_type) = detect_css_signature_type(&capture_map, code) else {
+        return custom_signature(query, captures, code);
+    };
     Some(signature_type.render())
 }
 
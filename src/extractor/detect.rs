@@ -0,0 +1,195 @@
+use super::SupportedLang;
+
+/// Bytes read from the start of a file when sniffing during the walk
+pub(super) const SNIFF_BYTES: usize = 1024;
+
+/// Lines inspected at each end of a file for editor modelines
+const MODELINE_LINES: usize = 5;
+
+/// `#!` interpreters, after stripping any version suffix (`ruby3.2` -> `ruby`)
+const INTERPRETERS: &[(&str, &str)] = &[
+    ("node", "js"),
+    ("nodejs", "js"),
+    ("bun", "js"),
+    ("deno", "ts"),
+    ("ts-node", "ts"),
+    ("tsx", "ts"),
+    ("ruby", "rb"),
+    ("jruby", "rb"),
+    ("php", "php"),
+    ("kotlin", "kts"),
+];
+
+/// Editor file-type names and common aliases accepted by modelines and `--lang-map`
+const LANGUAGE_NAMES: &[(&str, &str)] = &[
+    ("javascript", "js"),
+    ("javascriptreact", "jsx"),
+    ("typescript", "ts"),
+    ("typescriptreact", "tsx"),
+    ("ruby", "rb"),
+    ("kotlin", "kt"),
+    ("csharp", "cs"),
+    ("c++", "cpp"),
+];
+
+/// Registry key for a language name (`javascript`, `ruby`, `c++`) or extension (`js`)
+pub(super) fn language_key(name: &str) -> String {
+    let name = name.trim().trim_start_matches('.').to_ascii_lowercase();
+    if let Some((_, key)) = LANGUAGE_NAMES.iter().find(|(alias, _)| *alias == name) {
+        return key.to_string();
+    }
+    SupportedLang::ALL
+        .iter()
+        .find(|lang| lang.name() == name)
+        .and_then(|lang| lang.extensions().first())
+        .map(|ext| ext.to_string())
+        .unwrap_or(name)
+}
+
+/// Registry key (an extension, so plugins can be targeted too) from a `#!` line
+/// or a leading `<?php` tag
+pub(super) fn sniff_interpreter(code: &str) -> Option<String> {
+    let first_line = code.lines().next()?.trim_start_matches('\u{feff}');
+    if first_line.starts_with("<?php") {
+        return Some("php".to_string());
+    }
+
+    // `#!/usr/bin/env -S node --no-warnings` -> `node`
+    let mut words = first_line.strip_prefix("#!")?.split_whitespace();
+    let mut program = words.next()?.rsplit('/').next()?;
+    if program == "env" {
+        program = words.find(|w| !w.starts_with('-') && !w.contains('='))?;
+    }
+    let program = program.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+
+    INTERPRETERS
+        .iter()
+        .find(|(name, _)| *name == program)
+        .map(|(_, key)| key.to_string())
+}
+
+/// Key from a Vim (`vim: set ft=ruby:`) or Emacs (`-*- mode: ruby -*-`) modeline
+/// in the first or last few lines
+pub(super) fn sniff_modeline(code: &str) -> Option<String> {
+    let lines: Vec<&str> = code.lines().collect();
+    let tail_start = lines.len().saturating_sub(MODELINE_LINES);
+    let candidates = lines
+        .iter()
+        .take(MODELINE_LINES)
+        .chain(lines.iter().skip(tail_start.max(MODELINE_LINES)));
+
+    for line in candidates {
+        if let Some(name) = vim_filetype(line).or_else(|| emacs_mode(line)) {
+            return Some(language_key(name));
+        }
+    }
+    None
+}
+
+fn vim_filetype(line: &str) -> Option<&str> {
+    // The marker must start a word, so `index:` is not mistaken for `ex:`
+    let start = ["vim:", "vi:", "ex:"].iter().find_map(|marker| {
+        line.match_indices(marker)
+            .find(|(idx, _)| *idx == 0 || line[..*idx].ends_with(char::is_whitespace))
+            .map(|(idx, _)| idx + marker.len())
+    })?;
+    line[start..]
+        .split(|c: char| c.is_whitespace() || c == ':')
+        .find_map(|option| {
+            option
+                .strip_prefix("ft=")
+                .or_else(|| option.strip_prefix("filetype="))
+        })
+        .filter(|name| !name.is_empty())
+}
+
+fn emacs_mode(line: &str) -> Option<&str> {
+    let start = line.find("-*-")? + 3;
+    let end = start + line[start..].find("-*-")?;
+    let vars = line[start..end].trim();
+
+    // `-*- ruby -*-` or `-*- mode: ruby; coding: utf-8 -*-`
+    if !vars.contains(':') {
+        return Some(vars).filter(|v| !v.is_empty());
+    }
+    vars.split(';').find_map(|var| {
+        let (key, value) = var.split_once(':')?;
+        (key.trim().eq_ignore_ascii_case("mode")).then_some(value.trim())
+    })
+}
+
+/// Refine an extension-based key from the content: JavaScript files with a
+/// `@flow` pragma are parsed with the TSX grammar, which understands Flow-style
+/// type annotations alongside JSX
+pub(super) fn refine_by_content(key: &str, code: &str) -> Option<&'static str> {
+    let is_javascript = SupportedLang::JavaScript.extensions().contains(&key);
+    (is_javascript && has_flow_pragma(code)).then_some("tsx")
+}
+
+/// `// @flow` or `/* @flow */` in the leading comment block
+fn has_flow_pragma(code: &str) -> bool {
+    for line in code.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with("#!") {
+            continue;
+        }
+        let is_comment = line.starts_with("//") || line.starts_with("/*") || line.starts_with('*');
+        if !is_comment {
+            return false;
+        }
+        if line
+            .split(|c: char| c.is_whitespace() || c == '*' || c == '/')
+            .any(|word| word == "@flow")
+        {
+            return true;
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shebangs_name_the_interpreter() {
+        let key = |code: &str| sniff_interpreter(code);
+        assert_eq!(key("#!/usr/bin/env node\n"), Some("js".to_string()));
+        assert_eq!(key("#!/usr/bin/env -S deno run\n"), Some("ts".to_string()));
+        assert_eq!(key("#!/usr/local/bin/ruby3.2 -w\n"), Some("rb".to_string()));
+        assert_eq!(key("<?php\necho 1;\n"), Some("php".to_string()));
+        assert_eq!(key("#!/bin/sh\n"), None);
+        assert_eq!(key("// not a shebang\n"), None);
+    }
+
+    #[test]
+    fn modelines_name_the_language() {
+        let key = |code: &str| sniff_modeline(code);
+        assert_eq!(key("# vim: set ft=ruby:\nputs 1\n"), Some("rb".to_string()));
+        assert_eq!(
+            key("// -*- mode: javascript; coding: utf-8 -*-\n"),
+            Some("js".to_string())
+        );
+        assert_eq!(key("/* -*- c++ -*- */\n"), Some("cpp".to_string()));
+
+        let tail = format!("{}// vim: filetype=typescript\n", "x\n".repeat(20));
+        assert_eq!(key(&tail), Some("ts".to_string()));
+    }
+
+    #[test]
+    fn modeline_markers_must_start_a_word() {
+        assert_eq!(sniff_modeline("const index: ft=ruby = 1;\n"), None);
+        let middle = format!("{}# vim: ft=ruby\n{}", "x\n".repeat(10), "x\n".repeat(10));
+        assert_eq!(sniff_modeline(&middle), None);
+    }
+
+    #[test]
+    fn flow_pragma_switches_javascript_to_tsx() {
+        assert_eq!(
+            refine_by_content("js", "// @flow\nconst a = 1;\n"),
+            Some("tsx")
+        );
+        assert_eq!(refine_by_content("js", "const a = 1; // @flow\n"), None);
+        assert_eq!(refine_by_content("ts", "// @flow\n"), None);
+    }
+}
@@ -0,0 +1,169 @@
+use super::ParseDiagnostic;
+use crate::error::TalosResult;
+use chardetng::{EncodingDetector, Iso2022JpDetection, Utf8Detection};
+use encoding_rs::{Encoding, UTF_16BE, UTF_16LE, UTF_8};
+use std::fs;
+use std::path::Path;
+
+/// Share of NUL bytes at odd (or even) offsets that marks BOM-less UTF-16 text
+const UTF16_NUL_RATIO: f64 = 0.4;
+
+/// Source text decoded to UTF-8
+pub struct DecodedSource {
+    pub code: String,
+    /// Encoding name when the file was not plain BOM-less UTF-8, e.g. `windows-1252`
+    pub encoding: Option<String>,
+    /// First byte sequence that could not be decoded and was replaced with U+FFFD
+    pub lossy: Option<ParseDiagnostic>,
+}
+
+/// Read `path` as text: a BOM wins, then BOM-less UTF-16, then UTF-8, then the
+/// encoding guessed from the bytes. Undecodable sequences are replaced rather
+/// than failing the file.
+pub fn read_source(path: &Path) -> TalosResult<DecodedSource> {
+    let bytes = fs::read(path)?;
+    Ok(decode(&bytes))
+}
+
+fn decode(bytes: &[u8]) -> DecodedSource {
+    if let Some((encoding, bom_len)) = Encoding::for_bom(bytes) {
+        let name = if encoding == UTF_8 {
+            "UTF-8-BOM".to_string()
+        } else {
+            encoding.name().to_string()
+        };
+        return transcode(&bytes[bom_len..], encoding, Some(name));
+    }
+
+    // NUL-interleaved ASCII is valid UTF-8 too, so check for UTF-16 first
+    if let Some(encoding) = sniff_utf16(bytes) {
+        return transcode(bytes, encoding, Some(encoding.name().to_string()));
+    }
+
+    if let Ok(code) = std::str::from_utf8(bytes) {
+        return DecodedSource {
+            code: code.to_string(),
+            encoding: None,
+            lossy: None,
+        };
+    }
+
+    // A few stray bytes in otherwise UTF-8 text: keep UTF-8 and replace them
+    if mostly_utf8(bytes) {
+        return transcode(bytes, UTF_8, Some(UTF_8.name().to_string()));
+    }
+
+    let mut detector = EncodingDetector::new(Iso2022JpDetection::Deny);
+    detector.feed(bytes, true);
+    let encoding = detector.guess(None, Utf8Detection::Allow);
+    transcode(bytes, encoding, Some(encoding.name().to_string()))
+}
+
+fn transcode(bytes: &[u8], encoding: &'static Encoding, name: Option<String>) -> DecodedSource {
+    let (code, had_errors) = encoding.decode_without_bom_handling(bytes);
+    let code = code.into_owned();
+    let lossy = had_errors.then(|| failure_position(bytes, encoding));
+    DecodedSource {
+        code,
+        encoding: name,
+        lossy,
+    }
+}
+
+/// BOM-less UTF-16 is mostly ASCII with a NUL in every other byte
+fn sniff_utf16(bytes: &[u8]) -> Option<&'static Encoding> {
+    if bytes.len() < 2 || !bytes.len().is_multiple_of(2) {
+        return None;
+    }
+    let pairs = (bytes.len() / 2) as f64;
+    let high_nuls = bytes.chunks_exact(2).filter(|pair| pair[1] == 0).count() as f64;
+    let low_nuls = bytes.chunks_exact(2).filter(|pair| pair[0] == 0).count() as f64;
+
+    if high_nuls / pairs >= UTF16_NUL_RATIO && low_nuls == 0.0 {
+        Some(UTF_16LE)
+    } else if low_nuls / pairs >= UTF16_NUL_RATIO && high_nuls == 0.0 {
+        Some(UTF_16BE)
+    } else {
+        None
+    }
+}
+
+/// More valid multi-byte UTF-8 characters than invalid sequences
+fn mostly_utf8(bytes: &[u8]) -> bool {
+    let text = String::from_utf8_lossy(bytes);
+    let replaced = text.chars().filter(|c| *c == '\u{FFFD}').count();
+    let multibyte = text
+        .chars()
+        .filter(|c| !c.is_ascii() && *c != '\u{FFFD}')
+        .count();
+    multibyte > replaced
+}
+
+/// Where decoding first fails. The decoded text can hold genuine U+FFFD
+/// characters, so decode again without replacement and stop at the error.
+fn failure_position(bytes: &[u8], encoding: &'static Encoding) -> ParseDiagnostic {
+    let mut decoder = encoding.new_decoder_without_bom_handling();
+    let capacity = decoder
+        .max_utf8_buffer_length_without_replacement(bytes.len())
+        .unwrap_or(bytes.len());
+    let mut before = String::with_capacity(capacity);
+    let _ = decoder.decode_to_string_without_replacement(bytes, &mut before, true);
+    let line = before.matches('\n').count() + 1;
+    let column = before
+        .rsplit('\n')
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .count()
+        + 1;
+    ParseDiagnostic {
+        line,
+        column,
+        message: format!(
+            "invalid {} byte sequence replaced with U+FFFD",
+            encoding.name()
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn utf8_passes_through_untouched() {
+        let decoded = decode("const π = 3;\n".as_bytes());
+        assert_eq!(decoded.code, "const π = 3;\n");
+        assert!(decoded.encoding.is_none() && decoded.lossy.is_none());
+    }
+
+    #[test]
+    fn bom_picks_the_encoding() {
+        let decoded = decode(b"\xEF\xBB\xBFlet a;");
+        assert_eq!(decoded.code, "let a;");
+        assert_eq!(decoded.encoding.as_deref(), Some("UTF-8-BOM"));
+
+        let utf16: Vec<u8> = "let b;".encode_utf16().flat_map(u16::to_le_bytes).collect();
+        let decoded = decode(&utf16);
+        assert_eq!(decoded.code, "let b;");
+        assert_eq!(decoded.encoding.as_deref(), Some("UTF-16LE"));
+    }
+
+    #[test]
+    fn legacy_text_is_transcoded() {
+        let decoded = decode(b"// caf\xE9 cr\xE8me br\xFBl\xE9e\nlet a;\n");
+        assert_eq!(decoded.code, "// café crème brûlée\nlet a;\n");
+        assert!(decoded.encoding.is_some());
+        assert!(decoded.lossy.is_none());
+    }
+
+    #[test]
+    fn lossy_position_is_where_decoding_failed() {
+        // A literal U+FFFD on line 1 must not be mistaken for the bad byte on line 2
+        let mut bytes = "// \u{FFFD} é è ü\nlet x\n".as_bytes().to_vec();
+        bytes.insert(bytes.len() - 1, 0xFF);
+        let decoded = decode(&bytes);
+        let lossy = decoded.lossy.expect("invalid byte reported");
+        assert_eq!((lossy.line, lossy.column), (2, 6));
+    }
+}
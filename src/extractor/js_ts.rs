@@ -1,33 +1,60 @@
+use super::react::detect_components;
+use super::{
+    collect_definitions, collect_imports, custom_signature, parse_diagnostics, Extraction,
+    SupportedLang,
+};
 use crate::error::{TalosError, TalosResult};
 use std::collections::BTreeSet;
 use tree_sitter::{Language, Node, Parser, Query, QueryCapture, QueryCursor};
 
-fn lang_js() -> Language {
-    tree_sitter_javascript::language()
-}
-fn lang_ts() -> Language {
-    tree_sitter_typescript::language_typescript()
-}
-#[allow(dead_code)]
-fn lang_tsx() -> Language {
-    tree_sitter_typescript::language_tsx()
-}
-
-const UNIFIED_QUERY: &str = include_str!("../../queries/unified.scm");
+const JAVASCRIPT_QUERY: &str = include_str!("../../queries/javascript.scm");
+const TYPESCRIPT_QUERY: &str = include_str!("../../queries/typescript.scm");
+const IMPORTS_QUERY: &str = include_str!("../../queries/imports.scm");
+const DEFINITIONS_QUERY: &str = include_str!("../../queries/definitions.scm");
+const JAVASCRIPT_DEFINITIONS_QUERY: &str = include_str!("../../queries/javascript_definitions.scm");
+const TYPESCRIPT_DEFINITIONS_QUERY: &str = include_str!("../../queries/typescript_definitions.scm");
 
-pub fn extract_js(code: &str) -> TalosResult<Vec<String>> {
-    extract_with_query(code, lang_js(), UNIFIED_QUERY)
+pub fn extract_js(code: &str, query: Option<&str>) -> TalosResult<Extraction> {
+    let definitions = format!("{}\n{}", DEFINITIONS_QUERY, JAVASCRIPT_DEFINITIONS_QUERY);
+    extract_with_query(
+        code,
+        SupportedLang::JavaScript.grammar(),
+        query.unwrap_or(JAVASCRIPT_QUERY),
+        &definitions,
+        true,
+    )
 }
 
-pub fn extract_ts(code: &str) -> TalosResult<Vec<String>> {
-    extract_with_query(code, lang_ts(), UNIFIED_QUERY)
+pub fn extract_ts(code: &str, query: Option<&str>) -> TalosResult<Extraction> {
+    let definitions = format!("{}\n{}", DEFINITIONS_QUERY, TYPESCRIPT_DEFINITIONS_QUERY);
+    extract_with_query(
+        code,
+        SupportedLang::TypeScript.grammar(),
+        query.unwrap_or(TYPESCRIPT_QUERY),
+        &definitions,
+        false,
+    )
 }
 
-pub fn extract_tsx(code: &str) -> TalosResult<Vec<String>> {
-    extract_with_query(code, lang_tsx(), UNIFIED_QUERY)
+pub fn extract_tsx(code: &str, query: Option<&str>) -> TalosResult<Extraction> {
+    let definitions = format!("{}\n{}", DEFINITIONS_QUERY, TYPESCRIPT_DEFINITIONS_QUERY);
+    extract_with_query(
+        code,
+        SupportedLang::TypeScriptReact.grammar(),
+        query.unwrap_or(TYPESCRIPT_QUERY),
+        &definitions,
+        true,
+    )
 }
 
-fn extract_with_query(code: &str, lang: Language, query_str: &str) -> TalosResult<Vec<String>> {
+/// `jsx` enables React component detection for grammars that parse JSX
+fn extract_with_query(
+    code: &str,
+    lang: Language,
+    query_str: &str,
+    definitions_query_str: &str,
+    jsx: bool,
+) -> TalosResult<Extraction> {
     let mut parser = Parser::new();
     parser
         .set_language(lang)
@@ -37,17 +64,40 @@ fn extract_with_query(code: &str, lang: Language, query_str: &str) -> TalosResul
         .parse(code, None)
         .ok_or_else(|| TalosError::ScanError("Failed to parse code".to_string()))?;
 
+    let components = if jsx {
+        detect_components(tree.root_node(), code)
+    } else {
+        Vec::new()
+    };
+    let component_names: BTreeSet<&str> =
+        components.iter().map(|c| c.entry.name.as_str()).collect();
+
     let query = Query::new(lang, query_str)?;
     let mut cursor = QueryCursor::new();
     let mut signatures: BTreeSet<String> = BTreeSet::new();
 
     for query_match in cursor.matches(&query, tree.root_node(), code.as_bytes()) {
-        if let Some(signature) = create_signature(&query, query_match.captures, code) {
+        if let Some(signature) =
+            create_signature(&query, query_match.captures, code, &component_names)
+        {
             signatures.insert(signature);
         }
     }
+    signatures.extend(components.iter().map(|c| c.signature.clone()));
+
+    let imports_query = Query::new(lang, IMPORTS_QUERY)?;
+    let imports = collect_imports(&imports_query, tree.root_node(), code);
+    let definitions_query = Query::new(lang, definitions_query_str)?;
+    let definitions = collect_definitions(&definitions_query, tree.root_node(), code);
 
-    Ok(signatures.into_iter().collect())
+    Ok(Extraction {
+        signatures: signatures.into_iter().collect(),
+        imports,
+        components: components.into_iter().map(|c| c.entry).collect(),
+        definitions,
+        diagnostics: parse_diagnostics(tree.root_node(), code),
+        encoding: None,
+    })
 }
 
 #[derive(Debug)]
@@ -55,8 +105,14 @@ enum SignatureType {
     Class(String),
     Function(String, String),
     Method(String, String),
-    ArrowFunction(String, String),
-    FunctionExpression(String, String),
+    ArrowFunction(String, String, String),
+    FunctionExpression(String, String, String),
+    DefaultArrow(String),
+    DefaultFunction(String),
+    AssignedArrow(String, String),
+    AssignedFunction(String, String),
+    FieldArrow(String, String),
+    FieldFunction(String, String),
 }
 
 impl SignatureType {
@@ -65,10 +121,31 @@ impl SignatureType {
             Self::Class(name) => format!("class {}", name),
             Self::Function(name, params) => format!("function {}{}", name, params),
             Self::Method(name, params) => format!("method {}{}", name, params),
-            Self::ArrowFunction(name, params) => format!("const {} = {} =>", name, params),
-            Self::FunctionExpression(name, params) => {
-                format!("const {} = function {}", name, params)
+            Self::ArrowFunction(keyword, name, params) => {
+                format!("{} {} = {} =>", keyword, name, params)
+            }
+            Self::FunctionExpression(keyword, name, params) => {
+                format!("{} {} = function {}", keyword, name, params)
             }
+            Self::DefaultArrow(params) => format!("export default {} =>", params),
+            Self::DefaultFunction(params) => format!("export default function {}", params),
+            Self::AssignedArrow(target, params) => format!("{} = {} =>", target, params),
+            Self::AssignedFunction(target, params) => {
+                format!("{} = function {}", target, params)
+            }
+            Self::FieldArrow(name, params) => format!("field {} = {} =>", name, params),
+            Self::FieldFunction(name, params) => format!("field {} = function {}", name, params),
+        }
+    }
+
+    /// Declared name for top-level declarations (methods excluded)
+    fn declared_name(&self) -> Option<&str> {
+        match self {
+            Self::Class(name)
+            | Self::Function(name, _)
+            | Self::ArrowFunction(_, name, _)
+            | Self::FunctionExpression(_, name, _) => Some(name),
+            _ => None,
         }
     }
 }
@@ -95,10 +172,24 @@ impl<'a> CaptureMap<'a> {
     }
 }
 
-fn create_signature(query: &Query, captures: &[QueryCapture], code: &str) -> Option<String> {
+/// Declarations named in `components` are skipped; they get a `component` signature instead
+fn create_signature(
+    query: &Query,
+    captures: &[QueryCapture],
+    code: &str,
+    components: &BTreeSet<&str>,
+) -> Option<String> {
     let capture_map = CaptureMap::new(query, captures);
 
-    let signature_type = detect_signature_type(&capture_map, code)?;
+    let Some(signature_type) = detect_signature_type(&capture_map, code) else {
+        return custom_signature(query, captures, code);
+    };
+    if signature_type
+        .declared_name()
+        .is_some_and(|name| components.contains(name))
+    {
+        return None;
+    }
     Some(signature_type.render())
 }
 
@@ -126,10 +217,9 @@ fn detect_signature_type(captures: &CaptureMap, code: &str) -> Option<SignatureT
         (captures.get_node("mname"), captures.get_node("mparams"))
     {
         let name = extract_text(code, name_node.byte_range());
-        let params = extract_text(code, params_node.byte_range());
         return Some(SignatureType::Method(
             name.into_owned(),
-            params.into_owned(),
+            parameter_list(code, params_node),
         ));
     }
 
@@ -138,17 +228,76 @@ fn detect_signature_type(captures: &CaptureMap, code: &str) -> Option<SignatureT
         (captures.get_node("vname"), captures.get_node("vparams"))
     {
         let name = extract_text(code, name_node.byte_range());
-        let params = extract_text(code, params_node.byte_range());
+        let params = parameter_list(code, params_node);
+        // const/let from lexical declarations, otherwise var
+        let keyword = captures
+            .get_node("vdecl")
+            .and_then(|decl| decl.child_by_field_name("kind"))
+            .map(|kind| extract_text(code, kind.byte_range()).into_owned())
+            .unwrap_or_else(|| "var".to_string());
 
         if captures.has_capture("is_arrow") {
             return Some(SignatureType::ArrowFunction(
+                keyword,
                 name.into_owned(),
-                params.into_owned(),
+                params,
             ));
         } else {
             return Some(SignatureType::FunctionExpression(
+                keyword,
                 name.into_owned(),
-                params.into_owned(),
+                function_params(code, params_node),
+            ));
+        }
+    }
+
+    // Anonymous default export
+    if let Some(params_node) = captures.get_node("dparams") {
+        if captures.has_capture("is_arrow") {
+            return Some(SignatureType::DefaultArrow(parameter_list(
+                code,
+                params_node,
+            )));
+        } else {
+            return Some(SignatureType::DefaultFunction(function_params(
+                code,
+                params_node,
+            )));
+        }
+    }
+
+    // Function assigned to a member (module.exports, exports.foo, Foo.prototype.bar)
+    if let (Some(target_node), Some(params_node)) =
+        (captures.get_node("aname"), captures.get_node("aparams"))
+    {
+        let target = extract_text(code, target_node.byte_range()).into_owned();
+        if captures.has_capture("is_arrow") {
+            return Some(SignatureType::AssignedArrow(
+                target,
+                parameter_list(code, params_node),
+            ));
+        } else {
+            return Some(SignatureType::AssignedFunction(
+                target,
+                function_params(code, params_node),
+            ));
+        }
+    }
+
+    // Class property holding a function
+    if let (Some(name_node), Some(params_node)) =
+        (captures.get_node("pname"), captures.get_node("pparams"))
+    {
+        let name = extract_text(code, name_node.byte_range()).into_owned();
+        if captures.has_capture("is_arrow") {
+            return Some(SignatureType::FieldArrow(
+                name,
+                parameter_list(code, params_node),
+            ));
+        } else {
+            return Some(SignatureType::FieldFunction(
+                name,
+                function_params(code, params_node),
             ));
         }
     }
@@ -158,6 +307,27 @@ fn detect_signature_type(captures: &CaptureMap, code: &str) -> Option<SignatureT
 
 use std::borrow::Cow;
 
+/// Parameters in parentheses, also for a bare arrow parameter: `x => 1` gives `(x)`
+fn parameter_list(code: &str, node: Node) -> String {
+    let params = extract_text(code, node.byte_range());
+    if node.kind() == "formal_parameters" {
+        params.into_owned()
+    } else {
+        format!("({})", params)
+    }
+}
+
+/// A function expression's parameters, led by its name when it has one:
+/// `function save(a) {}` gives `save(a)`
+fn function_params(code: &str, params: Node) -> String {
+    let name = params
+        .parent()
+        .and_then(|function| function.child_by_field_name("name"))
+        .map(|name| extract_text(code, name.byte_range()).into_owned())
+        .unwrap_or_default();
+    format!("{}{}", name, parameter_list(code, params))
+}
+
 fn extract_text(code: &str, range: std::ops::Range<usize>) -> Cow<'_, str> {
     match code.get(range) {
         Some(s) => {
@@ -171,3 +341,34 @@ fn extract_text(code: &str, range: std::ops::Range<usize>) -> Cow<'_, str> {
         None => Cow::Borrowed(""),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn commonjs_exports_keep_names_and_bare_parameters() {
+        let code = "module.exports = function foo(a, b) {};\nexports.bar = x => 1;\n";
+        let signatures = extract_js(code, None).unwrap().signatures;
+        assert!(signatures.contains(&"module.exports = function foo(a, b)".to_string()));
+        assert!(signatures.contains(&"exports.bar = (x) =>".to_string()));
+    }
+
+    #[test]
+    fn bare_arrow_parameters_are_parenthesized() {
+        let code = "const inc = n => n + 1;\nconst o = { tap: v => v };\nexport default x => x;\n";
+        let signatures = extract_ts(code, None).unwrap().signatures;
+        assert!(signatures.contains(&"const inc = (n) =>".to_string()));
+        assert!(signatures.contains(&"method tap(v)".to_string()));
+        assert!(signatures.contains(&"export default (x) =>".to_string()));
+    }
+
+    #[test]
+    fn shipped_queries_are_complete_overrides() {
+        let code = "function f(a) {}\nclass C { m(b) {} }\n";
+        let built_in = extract_js(code, None).unwrap().signatures;
+        let overridden = extract_js(code, Some(JAVASCRIPT_QUERY)).unwrap().signatures;
+        assert_eq!(built_in, overridden);
+        assert!(built_in.contains(&"function f(a)".to_string()));
+    }
+}
@@ -0,0 +1,170 @@
+use super::{
+    attributed_header, collect_definitions, collect_imports, custom_signature, parse_diagnostics,
+    Extraction, SupportedLang,
+};
+use crate::error::{TalosError, TalosResult};
+use std::collections::BTreeSet;
+use tree_sitter::{Language, Node, Parser, Query, QueryCapture, QueryCursor};
+
+const JAVA_QUERY: &str = include_str!("../../queries/java.scm");
+const JAVA_IMPORTS_QUERY: &str = include_str!("../../queries/java_imports.scm");
+const KOTLIN_QUERY: &str = include_str!("../../queries/kotlin.scm");
+const KOTLIN_IMPORTS_QUERY: &str = include_str!("../../queries/kotlin_imports.scm");
+const JAVA_DEFINITIONS_QUERY: &str = include_str!("../../queries/java_definitions.scm");
+const KOTLIN_DEFINITIONS_QUERY: &str = include_str!("../../queries/kotlin_definitions.scm");
+
+/// Kotlin nodes carry no `body` field, so bodies are found by kind
+const KOTLIN_BODIES: &[&str] = &["class_body", "enum_class_body", "function_body"];
+
+pub fn extract_java(code: &str, query: Option<&str>) -> TalosResult<Extraction> {
+    extract_with_query(
+        code,
+        SupportedLang::Java.grammar(),
+        query.unwrap_or(JAVA_QUERY),
+        JAVA_IMPORTS_QUERY,
+        JAVA_DEFINITIONS_QUERY,
+    )
+}
+
+pub fn extract_kotlin(code: &str, query: Option<&str>) -> TalosResult<Extraction> {
+    extract_with_query(
+        code,
+        SupportedLang::Kotlin.grammar(),
+        query.unwrap_or(KOTLIN_QUERY),
+        KOTLIN_IMPORTS_QUERY,
+        KOTLIN_DEFINITIONS_QUERY,
+    )
+}
+
+fn extract_with_query(
+    code: &str,
+    lang: Language,
+    query_str: &str,
+    imports_query_str: &str,
+    definitions_query_str: &str,
+) -> TalosResult<Extraction> {
+    let mut parser = Parser::new();
+    parser
+        .set_language(lang)
+        .map_err(|e| TalosError::ScanError(format!("Failed to set parser language: {}", e)))?;
+
+    let tree = parser
+        .parse(code, None)
+        .ok_or_else(|| TalosError::ScanError("Failed to parse code".to_string()))?;
+
+    let query = Query::new(lang, query_str)?;
+    let mut cursor = QueryCursor::new();
+    let mut signatures: BTreeSet<String> = BTreeSet::new();
+
+    for query_match in cursor.matches(&query, tree.root_node(), code.as_bytes()) {
+        if let Some(signature) = create_jvm_signature(&query, query_match.captures, code) {
+            signatures.insert(signature);
+        }
+    }
+
+    let imports_query = Query::new(lang, imports_query_str)?;
+    let imports = collect_imports(&imports_query, tree.root_node(), code);
+    let definitions_query = Query::new(lang, definitions_query_str)?;
+    let definitions = collect_definitions(&definitions_query, tree.root_node(), code);
+
+    Ok(Extraction {
+        signatures: signatures.into_iter().collect(),
+        imports,
+        components: Vec::new(),
+        definitions,
+        diagnostics: parse_diagnostics(tree.root_node(), code),
+        encoding: None,
+    })
+}
+
+#[derive(Debug)]
+enum JvmSignatureType {
+    Package(String),
+    Declaration(String),
+}
+
+impl JvmSignatureType {
+    fn render(self) -> String {
+        match self {
+            Self::Package(name) => format!("package {}", name),
+            Self::Declaration(header) => header,
+        }
+    }
+}
+
+struct JvmCaptureMap<'a> {
+    query: &'a Query,
+    captures: &'a [QueryCapture<'a>],
+}
+
+impl<'a> JvmCaptureMap<'a> {
+    fn new(query: &'a Query, captures: &'a [QueryCapture<'a>]) -> Self {
+        Self { query, captures }
+    }
+
+    fn get_node(&self, name: &str) -> Option<Node<'a>> {
+        self.captures
+            .iter()
+            .find(|c| self.query.capture_names()[c.index as usize] == name)
+            .map(|c| c.node)
+    }
+}
+
+fn create_jvm_signature(query: &Query, captures: &[QueryCapture], code: &str) -> Option<String> {
+    let capture_map = JvmCaptureMap::new(query, captures);
+    let Some(signature_type) = detect_jvm_signature_type(&capture_map, code) else {
+        return custom_signature(query, captures, code);
+    };
+    Some(signature_type.render())
+}
+
+fn detect_jvm_signature_type(captures: &JvmCaptureMap, code: &str) -> Option<JvmSignatureType> {
+    // Package
+    if let Some(node) = captures.get_node("package") {
+        let name = code.get(node.byte_range()).unwrap_or_default().trim();
+        return Some(JvmSignatureType::Package(name.to_string()));
+    }
+
+    // Class, interface, record, enum, object, method, constructor, function:
+    // the declaration text up to its body, keeping annotations, modifiers,
+    // generics and parameters
+    if let Some(node) = captures.get_node("declaration") {
+        let header = attributed_header(node, code, KOTLIN_BODIES);
+        if !header.is_empty() {
+            return Some(JvmSignatureType::Declaration(header));
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn java_declarations_keep_their_annotations() {
+        let code = r#"
+@RestController
+public class Api {
+    @GetMapping("/x/{id}")
+    public String get(@PathVariable long id) { return ""; }
+}
+"#;
+        let signatures = extract_java(code, None).unwrap().signatures;
+        assert!(signatures.contains(&"@RestController public class Api".to_string()));
+        assert!(signatures.contains(
+            &r#"@GetMapping("/x/{id}") public String get(@PathVariable long id)"#.to_string()
+        ));
+    }
+
+    #[test]
+    fn kotlin_declarations_keep_their_annotations() {
+        let code = "@Serializable\ndata class User(val id: Long)\n";
+        let signatures = extract_kotlin(code, None).unwrap().signatures;
+        assert_eq!(
+            signatures,
+            vec!["@Serializable data class User(val id: Long)".to_string()]
+        );
+    }
+}
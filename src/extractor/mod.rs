@@ -1,54 +1,487 @@
-use crate::error::{TalosError, TalosResult};
-use std::fs;
+use crate::error::TalosResult;
+use crate::model::{ComponentEntry, DefinitionEntry};
+use serde::Deserialize;
+use std::collections::{BTreeMap, BTreeSet};
 use std::path::Path;
+use tree_sitter::{Language, Node, Query, QueryCapture, QueryCursor};
 
+mod c_family;
+mod csharp;
 mod css;
+mod detect;
+mod encoding;
 mod js_ts;
+mod jvm;
+mod php;
+mod plugin;
+mod queries;
+mod react;
+mod registry;
+mod ruby;
 
-#[derive(Debug, Clone, Copy)]
+pub use c_family::{declared_symbol, CSymbol};
+pub use encoding::{read_source, DecodedSource};
+pub use plugin::SubprocessExtractor;
+pub use queries::QueryOverrides;
+pub use registry::ExtractorRegistry;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum SupportedLang {
     JavaScript,
     TypeScript,
     TypeScriptReact,
     CSS,
+    Java,
+    Kotlin,
+    C,
+    Cpp,
+    CSharp,
+    Php,
+    Ruby,
+}
+
+impl SupportedLang {
+    pub const ALL: &'static [SupportedLang] = &[
+        SupportedLang::JavaScript,
+        SupportedLang::TypeScript,
+        SupportedLang::TypeScriptReact,
+        SupportedLang::CSS,
+        SupportedLang::Java,
+        SupportedLang::Kotlin,
+        SupportedLang::C,
+        SupportedLang::Cpp,
+        SupportedLang::CSharp,
+        SupportedLang::Php,
+        SupportedLang::Ruby,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            SupportedLang::JavaScript => "javascript",
+            SupportedLang::TypeScript => "typescript",
+            SupportedLang::TypeScriptReact => "tsx",
+            SupportedLang::CSS => "css",
+            SupportedLang::Java => "java",
+            SupportedLang::Kotlin => "kotlin",
+            SupportedLang::C => "c",
+            SupportedLang::Cpp => "cpp",
+            SupportedLang::CSharp => "csharp",
+            SupportedLang::Php => "php",
+            SupportedLang::Ruby => "ruby",
+        }
+    }
+
+    /// File extensions (lowercase, without dot) handled by this language.
+    ///
+    /// `.h` headers are parsed as C++, which accepts nearly all C declarations.
+    pub fn extensions(self) -> &'static [&'static str] {
+        match self {
+            SupportedLang::JavaScript => &["js", "jsx", "mjs", "cjs"],
+            SupportedLang::TypeScript => &["ts", "mts", "cts"],
+            SupportedLang::TypeScriptReact => &["tsx"],
+            SupportedLang::CSS => &["css"],
+            SupportedLang::Java => &["java"],
+            SupportedLang::Kotlin => &["kt", "kts"],
+            SupportedLang::C => &["c"],
+            SupportedLang::Cpp => &["cpp", "cc", "cxx", "h", "hpp", "hh", "hxx"],
+            SupportedLang::CSharp => &["cs"],
+            SupportedLang::Php => &["php"],
+            SupportedLang::Ruby => &["rb", "rake", "gemspec"],
+        }
+    }
+
+    /// Extensionless file names handled by this language
+    pub fn file_names(self) -> &'static [&'static str] {
+        match self {
+            SupportedLang::Ruby => &["Rakefile", "Gemfile"],
+            _ => &[],
+        }
+    }
+
+    /// Tree-sitter grammar used to parse (and validate queries for) this language
+    pub fn grammar(self) -> Language {
+        match self {
+            SupportedLang::JavaScript => tree_sitter_javascript::language(),
+            SupportedLang::TypeScript => tree_sitter_typescript::language_typescript(),
+            SupportedLang::TypeScriptReact => tree_sitter_typescript::language_tsx(),
+            SupportedLang::CSS => tree_sitter_css::language(),
+            SupportedLang::Java => tree_sitter_java::language(),
+            SupportedLang::Kotlin => tree_sitter_kotlin::language(),
+            SupportedLang::C => tree_sitter_c::language(),
+            SupportedLang::Cpp => tree_sitter_cpp::language(),
+            SupportedLang::CSharp => tree_sitter_c_sharp::language(),
+            SupportedLang::Php => tree_sitter_php::language_php(),
+            SupportedLang::Ruby => tree_sitter_ruby::language(),
+        }
+    }
+}
+
+/// Everything extracted from a single source file
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Extraction {
+    pub signatures: Vec<String>,
+    /// Raw module specifiers as written in the source (e.g. `./utils`, `react`)
+    pub imports: Vec<String>,
+    /// React components detected in JSX-capable sources
+    pub components: Vec<ComponentEntry>,
+    /// Named declarations with their positions, for symbol indexes
+    pub definitions: Vec<DefinitionEntry>,
+    /// Syntax or decoding errors that were recovered from; signatures may be incomplete
+    pub diagnostics: Vec<ParseDiagnostic>,
+    /// Source encoding when the file was not plain UTF-8; set by the registry
+    #[serde(skip)]
+    pub encoding: Option<String>,
+}
+
+/// A syntax error, missing token or undecodable byte sequence in a file, 1-based
+#[derive(Debug, Clone, Deserialize)]
+pub struct ParseDiagnostic {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+/// A source language extractor. Implement this and register it with
+/// [`ExtractorRegistry::register`] to add languages from outside the crate.
+pub trait LanguageExtractor: Send + Sync {
+    /// Short language name used in error messages, e.g. `"javascript"`
+    fn name(&self) -> &str;
+
+    /// `path` is informational; extractors should work from `code` alone
+    fn extract(&self, path: &Path, code: &str) -> TalosResult<Extraction>;
 }
 
-pub trait LanguageExtractor {
-    fn extract(&self, code: &str) -> TalosResult<Vec<String>>;
+/// Built-in tree-sitter extractor for one of the [`SupportedLang`] variants
+struct BuiltinExtractor {
+    lang: SupportedLang,
+    /// User override replacing the built-in signature query
+    query: Option<String>,
+}
+
+impl LanguageExtractor for BuiltinExtractor {
+    fn name(&self) -> &str {
+        self.lang.name()
+    }
+
+    fn extract(&self, _path: &Path, code: &str) -> TalosResult<Extraction> {
+        extract_for_language(code, self.lang, self.query.as_deref())
+    }
 }
 
 pub fn infer_lang_from_ext(path: &Path) -> Option<SupportedLang> {
     let ext = path
         .extension()
         .map(|e| e.to_string_lossy().to_ascii_lowercase())?;
-    match ext.as_str() {
-        "js" | "jsx" => Some(SupportedLang::JavaScript),
-        "ts" => Some(SupportedLang::TypeScript),
-        "tsx" => Some(SupportedLang::TypeScriptReact),
-        "css" => Some(SupportedLang::CSS),
-        _ => None,
+    SupportedLang::ALL
+        .iter()
+        .copied()
+        .find(|lang| lang.extensions().contains(&ext.as_str()))
+}
+
+/// `query` replaces the built-in signature query when given
+fn extract_for_language(
+    code: &str,
+    lang: SupportedLang,
+    query: Option<&str>,
+) -> TalosResult<Extraction> {
+    match lang {
+        SupportedLang::JavaScript => js_ts::extract_js(code, query),
+        SupportedLang::TypeScript => js_ts::extract_ts(code, query),
+        SupportedLang::TypeScriptReact => js_ts::extract_tsx(code, query),
+        SupportedLang::CSS => css::extract_css(code, query),
+        SupportedLang::Java => jvm::extract_java(code, query),
+        SupportedLang::Kotlin => jvm::extract_kotlin(code, query),
+        SupportedLang::C => c_family::extract_c(code, query),
+        SupportedLang::Cpp => c_family::extract_cpp(code, query),
+        SupportedLang::CSharp => csharp::extract_csharp(code, query),
+        SupportedLang::Php => php::extract_php(code, query),
+        SupportedLang::Ruby => ruby::extract_ruby(code, query),
+    }
+}
+
+/// Annotation node kinds dropped from declaration headers
+const ANNOTATION_KINDS: &[&str] = &["annotation", "marker_annotation", "attribute_list"];
+
+/// Declaration text up to (not including) its body, with annotations removed and
+/// whitespace collapsed, e.g. `public static <T> List<T> of(T... items)`.
+///
+/// The body is the `body` field when the grammar has one, otherwise the first
+/// child whose kind is in `body_kinds`.
+fn declaration_header(node: Node, code: &str, body_kinds: &[&str]) -> String {
+    header_text(node, code, body_kinds, true)
+}
+
+/// Like [`declaration_header`], but keeps annotations and attributes, e.g.
+/// `[HttpGet("{id}")] public Task<Order> Get(int id)`
+fn attributed_header(node: Node, code: &str, body_kinds: &[&str]) -> String {
+    header_text(node, code, body_kinds, false)
+}
+
+fn header_text(node: Node, code: &str, body_kinds: &[&str], strip_annotations: bool) -> String {
+    let body = node.child_by_field_name("body").or_else(|| {
+        let mut cursor = node.walk();
+        let found = node
+            .named_children(&mut cursor)
+            .find(|c| body_kinds.contains(&c.kind()));
+        found
+    });
+    let end = body
+        .map(|b| b.start_byte())
+        .unwrap_or_else(|| node.end_byte());
+
+    // Byte ranges of annotations, which live in leading modifier lists
+    let mut skipped: Vec<std::ops::Range<usize>> = Vec::new();
+    if strip_annotations {
+        collect_annotation_ranges(node, end, &mut skipped);
     }
+
+    let mut header = String::new();
+    let mut pos = node.start_byte();
+    for range in skipped {
+        header.push_str(code.get(pos..range.start).unwrap_or_default());
+        header.push(' ');
+        pos = range.end;
+    }
+    header.push_str(code.get(pos..end).unwrap_or_default());
+
+    let header = header.split_whitespace().collect::<Vec<_>>().join(" ");
+    header.trim_end_matches([';', '{', '=', ' ']).to_string()
+}
+
+fn collect_annotation_ranges(node: Node, end: usize, ranges: &mut Vec<std::ops::Range<usize>>) {
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        if child.start_byte() >= end {
+            break;
+        }
+        if ANNOTATION_KINDS.contains(&child.kind()) {
+            ranges.push(child.byte_range());
+        } else if child.kind() == "modifiers" {
+            collect_annotation_ranges(child, end, ranges);
+        }
+    }
+}
+
+/// Join every `@signature*` capture (e.g. `@signature`, `@signature.path`) in
+/// capture order. Lets custom queries emit signatures without a Rust renderer.
+fn custom_signature(query: &Query, captures: &[QueryCapture], code: &str) -> Option<String> {
+    let parts: Vec<String> = captures
+        .iter()
+        .filter(|c| query.capture_names()[c.index as usize].starts_with("signature"))
+        .filter_map(|c| code.get(c.node.byte_range()))
+        .map(|text| text.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|text| !text.is_empty())
+        .collect();
+
+    if parts.is_empty() {
+        None
+    } else {
+        Some(parts.join(" "))
+    }
+}
+
+/// At most this many diagnostics are reported per file; later errors are
+/// usually fallout from the first
+const MAX_PARSE_DIAGNOSTICS: usize = 10;
+
+/// ERROR and MISSING nodes in a parsed tree, in source order
+fn parse_diagnostics(root: Node, code: &str) -> Vec<ParseDiagnostic> {
+    let mut diagnostics = Vec::new();
+    if root.has_error() {
+        collect_diagnostics(root, code, &mut diagnostics);
+    }
+    diagnostics
 }
 
-pub fn extract_signatures_for_file(path: &Path) -> TalosResult<Vec<String>> {
-    let lang = match infer_lang_from_ext(path) {
-        Some(l) => l,
-        None => return Ok(vec![]),
+fn collect_diagnostics(node: Node, code: &str, diagnostics: &mut Vec<ParseDiagnostic>) {
+    if diagnostics.len() >= MAX_PARSE_DIAGNOSTICS {
+        return;
+    }
+
+    let message = if node.is_missing() {
+        Some(format!("missing `{}`", node.kind()))
+    } else if node.is_error() {
+        let text = code.get(node.byte_range()).unwrap_or_default();
+        let snippet: String = text
+            .lines()
+            .next()
+            .unwrap_or_default()
+            .chars()
+            .take(40)
+            .collect();
+        Some(format!("syntax error near `{}`", snippet.trim()))
+    } else {
+        None
     };
 
-    let code = read_file_safely(path)?;
-    extract_signatures_for_language(&code, lang)
+    if let Some(message) = message {
+        let start = node.start_position();
+        diagnostics.push(ParseDiagnostic {
+            line: start.row + 1,
+            column: start.column + 1,
+            message,
+        });
+        // Errors nested in an ERROR node are part of the same failure
+        if node.is_error() {
+            return;
+        }
+    }
+
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        if child.has_error() || child.is_missing() {
+            collect_diagnostics(child, code, diagnostics);
+        }
+    }
 }
 
-fn read_file_safely(path: &Path) -> TalosResult<String> {
-    fs::read_to_string(path).map_err(TalosError::Io)
+/// Collect every `@import_source` capture, with surrounding quotes stripped
+fn collect_imports(query: &Query, root: Node, code: &str) -> Vec<String> {
+    let mut cursor = QueryCursor::new();
+    let mut imports: BTreeSet<String> = BTreeSet::new();
+
+    for query_match in cursor.matches(query, root, code.as_bytes()) {
+        for capture in query_match.captures {
+            if query.capture_names()[capture.index as usize] != "import_source" {
+                continue;
+            }
+            let raw = code.get(capture.node.byte_range()).unwrap_or_default();
+            let specifier = raw
+                .trim()
+                .trim_matches(|c| c == '"' || c == '\'' || c == '`');
+            if !specifier.is_empty() {
+                imports.insert(specifier.to_string());
+            }
+        }
+    }
+
+    imports.into_iter().collect()
 }
 
-fn extract_signatures_for_language(code: &str, lang: SupportedLang) -> TalosResult<Vec<String>> {
-    match lang {
-        SupportedLang::JavaScript => js_ts::extract_js(code),
-        SupportedLang::TypeScript => js_ts::extract_ts(code),
-        SupportedLang::TypeScriptReact => js_ts::extract_tsx(code),
-        SupportedLang::CSS => css::extract_css(code),
+/// A `@name` capture with the `@definition.<kind>` node around it
+struct FoundDefinition {
+    entry: DefinitionEntry,
+    pattern: usize,
+    range: std::ops::Range<usize>,
+}
+
+/// Every `@name` capture paired with its match's `@definition.<kind>` capture,
+/// in source order. When several patterns match one name the first pattern
+/// wins, and each definition is scoped to the innermost one enclosing it.
+fn collect_definitions(query: &Query, root: Node, code: &str) -> Vec<DefinitionEntry> {
+    let mut cursor = QueryCursor::new();
+    let mut found: BTreeMap<usize, FoundDefinition> = BTreeMap::new();
+
+    for query_match in cursor.matches(query, root, code.as_bytes()) {
+        let mut name = None;
+        let mut definition = None;
+        for capture in query_match.captures {
+            let capture_name = &query.capture_names()[capture.index as usize];
+            if capture_name == "name" {
+                name = Some(capture.node);
+            } else if let Some(kind) = capture_name.strip_prefix("definition.") {
+                definition = Some((kind, capture.node));
+            }
+        }
+        let (Some(name), Some((kind, node))) = (name, definition) else {
+            continue;
+        };
+        let text = code.get(name.byte_range()).unwrap_or_default().trim();
+        if text.is_empty() || text.contains(['\n', '\t']) {
+            continue;
+        }
+        if found
+            .get(&name.start_byte())
+            .is_some_and(|existing| existing.pattern <= query_match.pattern_index)
+        {
+            continue;
+        }
+
+        let line_start = code[..name.start_byte()]
+            .rfind('\n')
+            .map(|i| i + 1)
+            .unwrap_or(0);
+        let column = code
+            .get(line_start..name.start_byte())
+            .map(|prefix| prefix.encode_utf16().count())
+            .unwrap_or(name.start_position().column);
+        found.insert(
+            name.start_byte(),
+            FoundDefinition {
+                entry: DefinitionEntry {
+                    name: text.to_string(),
+                    kind: kind.to_string(),
+                    line: name.start_position().row + 1,
+                    column: column + 1,
+                    scope: None,
+                },
+                pattern: query_match.pattern_index,
+                range: node.byte_range(),
+            },
+        );
+    }
+
+    let found: Vec<FoundDefinition> = found.into_values().collect();
+
+    // Visit definitions outermost-first, keeping the ones still open on a stack;
+    // the innermost open one that isn't the same node is the scope
+    let mut order: Vec<usize> = (0..found.len()).collect();
+    order.sort_by_key(|&idx| {
+        (
+            found[idx].range.start,
+            std::cmp::Reverse(found[idx].range.end),
+        )
+    });
+    let mut scopes: Vec<Option<String>> = vec![None; found.len()];
+    let mut open: Vec<usize> = Vec::new();
+    for idx in order {
+        let range = &found[idx].range;
+        while open
+            .last()
+            .is_some_and(|&outer| found[outer].range.end < range.end)
+        {
+            open.pop();
+        }
+        scopes[idx] = open
+            .iter()
+            .rev()
+            .map(|&outer| &found[outer])
+            .find(|outer| outer.range != *range)
+            .map(|outer| format!("{}:{}", outer.entry.kind, outer.entry.name));
+        open.push(idx);
+    }
+
+    found
+        .into_iter()
+        .zip(scopes)
+        .map(|(def, scope)| DefinitionEntry { scope, ..def.entry })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn definitions_are_scoped_to_the_innermost_enclosing_one() {
+        let code = "class Outer {\n  run() {\n    function inner() {}\n  }\n  stop() {}\n}\nfunction top() {}\n";
+        let scopes: Vec<(String, Option<String>)> = js_ts::extract_ts(code, None)
+            .unwrap()
+            .definitions
+            .into_iter()
+            .map(|def| (def.name, def.scope))
+            .collect();
+        let scope = |s: &str| Some(s.to_string());
+        assert_eq!(
+            scopes,
+            vec![
+                ("Outer".to_string(), None),
+                ("run".to_string(), scope("class:Outer")),
+                ("inner".to_string(), scope("method:run")),
+                ("stop".to_string(), scope("class:Outer")),
+                ("top".to_string(), None),
+            ]
+        );
     }
 }
@@ -0,0 +1,142 @@
+use super::{
+    collect_definitions, collect_imports, custom_signature, declaration_header, parse_diagnostics,
+    Extraction, SupportedLang,
+};
+use crate::error::{TalosError, TalosResult};
+use std::collections::BTreeSet;
+use tree_sitter::{Node, Parser, Query, QueryCapture, QueryCursor};
+
+const PHP_QUERY: &str = include_str!("../../queries/php.scm");
+const PHP_IMPORTS_QUERY: &str = include_str!("../../queries/php_imports.scm");
+const PHP_DEFINITIONS_QUERY: &str = include_str!("../../queries/php_definitions.scm");
+
+pub fn extract_php(code: &str, query: Option<&str>) -> TalosResult<Extraction> {
+    let lang = SupportedLang::Php.grammar();
+    let mut parser = Parser::new();
+    parser
+        .set_language(lang)
+        .map_err(|e| TalosError::ScanError(format!("Failed to set parser language: {}", e)))?;
+
+    let tree = parser
+        .parse(code, None)
+        .ok_or_else(|| TalosError::ScanError("Failed to parse code".to_string()))?;
+
+    let query = Query::new(lang, query.unwrap_or(PHP_QUERY))?;
+    let mut cursor = QueryCursor::new();
+    let mut signatures: BTreeSet<String> = BTreeSet::new();
+
+    for query_match in cursor.matches(&query, tree.root_node(), code.as_bytes()) {
+        if let Some(signature) = create_php_signature(&query, query_match.captures, code) {
+            signatures.insert(signature);
+        }
+    }
+
+    let imports_query = Query::new(lang, PHP_IMPORTS_QUERY)?;
+    let imports = collect_imports(&imports_query, tree.root_node(), code);
+    let definitions_query = Query::new(lang, PHP_DEFINITIONS_QUERY)?;
+    let definitions = collect_definitions(&definitions_query, tree.root_node(), code);
+
+    Ok(Extraction {
+        signatures: signatures.into_iter().collect(),
+        imports,
+        components: Vec::new(),
+        definitions,
+        diagnostics: parse_diagnostics(tree.root_node(), code),
+        encoding: None,
+    })
+}
+
+#[derive(Debug)]
+enum PhpSignatureType {
+    Namespace(String),
+    Declaration(String),
+}
+
+impl PhpSignatureType {
+    fn render(self) -> String {
+        match self {
+            Self::Namespace(name) => format!("namespace {}", name),
+            Self::Declaration(header) => header,
+        }
+    }
+}
+
+struct PhpCaptureMap<'a> {
+    query: &'a Query,
+    captures: &'a [QueryCapture<'a>],
+}
+
+impl<'a> PhpCaptureMap<'a> {
+    fn new(query: &'a Query, captures: &'a [QueryCapture<'a>]) -> Self {
+        Self { query, captures }
+    }
+
+    fn get_node(&self, name: &str) -> Option<Node<'a>> {
+        self.captures
+            .iter()
+            .find(|c| self.query.capture_names()[c.index as usize] == name)
+            .map(|c| c.node)
+    }
+}
+
+fn create_php_signature(query: &Query, captures: &[QueryCapture], code: &str) -> Option<String> {
+    let capture_map = PhpCaptureMap::new(query, captures);
+    let Some(signature_type) = detect_php_signature_type(&capture_map, code) else {
+        return custom_signature(query, captures, code);
+    };
+    Some(signature_type.render())
+}
+
+fn detect_php_signature_type(captures: &PhpCaptureMap, code: &str) -> Option<PhpSignatureType> {
+    // namespace App\Models
+    if let Some(node) = captures.get_node("namespace") {
+        let name = code.get(node.byte_range()).unwrap_or_default().trim();
+        return Some(PhpSignatureType::Namespace(name.to_string()));
+    }
+
+    // Classes, traits, functions, methods, typed properties and trait `use`:
+    // the declaration up to its body, keeping modifiers and type declarations
+    if let Some(node) = captures.get_node("declaration") {
+        let header = declaration_header(node, code, &[]);
+        if !header.is_empty() {
+            return Some(PhpSignatureType::Declaration(header));
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn namespaces_traits_and_imports() {
+        let code = r#"<?php
+namespace App\Models;
+use Illuminate\Support\Str;
+trait HasSlug { public function slug(): string { return ""; } }
+class Post extends Model {
+    use HasSlug;
+    public function title(int $n = 1): ?string { return null; }
+}
+"#;
+        let extraction = extract_php(code, None).unwrap();
+        for expected in [
+            r"namespace App\Models",
+            "trait HasSlug",
+            "public function slug(): string",
+            "class Post extends Model",
+            "use HasSlug",
+            "public function title(int $n = 1): ?string",
+        ] {
+            assert!(
+                extraction.signatures.contains(&expected.to_string()),
+                "missing {expected:?} in {:?}",
+                extraction.signatures
+            );
+        }
+        // A trait `use` inside a class is not an import
+        assert_eq!(extraction.imports, vec![r"Illuminate\Support\Str"]);
+    }
+}
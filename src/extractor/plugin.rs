@@ -0,0 +1,234 @@
+use super::{Extraction, LanguageExtractor};
+use crate::error::{TalosError, TalosResult};
+use crate::model::ErrorKind;
+use serde::{Deserialize, Serialize};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+/// How long a plugin may take to answer one request before it is stopped
+pub const DEFAULT_PLUGIN_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Out-of-process extractor speaking line-delimited JSON over stdin/stdout.
+///
+/// The command is started once, on first use, and receives one request per line:
+/// `{"path": "/abs/path/src/app.py", "code": "..."}`. It must answer each request
+/// with one line: `{"signatures": [...], "imports": [...]}` or `{"error": "message"}`.
+/// A plugin that does not answer in time is killed; if the process fails it is
+/// restarted for the next file.
+pub struct SubprocessExtractor {
+    name: String,
+    program: String,
+    args: Vec<String>,
+    timeout: Duration,
+    process: Mutex<Option<PluginProcess>>,
+}
+
+/// A running plugin. Its pipes live on a worker thread, so a silent plugin
+/// can be waited on with a deadline.
+struct PluginProcess {
+    child: Child,
+    requests: Sender<String>,
+    responses: Receiver<io::Result<String>>,
+}
+
+#[derive(Serialize)]
+struct PluginRequest<'a> {
+    path: &'a str,
+    code: &'a str,
+}
+
+#[derive(Deserialize)]
+struct PluginResponse {
+    #[serde(flatten)]
+    extraction: Extraction,
+    #[serde(default)]
+    error: Option<String>,
+}
+
+impl SubprocessExtractor {
+    /// `command` is split on whitespace (no shell); the first word is the program
+    pub fn new(name: impl Into<String>, command: &str) -> TalosResult<Self> {
+        let mut words = command.split_whitespace().map(String::from);
+        let program = words
+            .next()
+            .ok_or_else(|| TalosError::InvalidInput("Empty plugin command".to_string()))?;
+
+        Ok(SubprocessExtractor {
+            name: name.into(),
+            program,
+            args: words.collect(),
+            timeout: DEFAULT_PLUGIN_TIMEOUT,
+            process: Mutex::new(None),
+        })
+    }
+
+    /// Deadline for each response, instead of [`DEFAULT_PLUGIN_TIMEOUT`]
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    fn spawn(&self) -> TalosResult<PluginProcess> {
+        let mut child = Command::new(&self.program)
+            .args(&self.args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()
+            .map_err(|e| {
+                self.error(
+                    ErrorKind::Io,
+                    format!("failed to start '{}': {}", self.program, e),
+                )
+            })?;
+
+        let (Some(mut stdin), Some(stdout)) = (child.stdin.take(), child.stdout.take()) else {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(self.error(ErrorKind::Io, "failed to open plugin pipes".to_string()));
+        };
+        let (requests, request_rx) = mpsc::channel::<String>();
+        let (response_tx, responses) = mpsc::channel();
+        thread::spawn(move || {
+            let mut stdout = BufReader::new(stdout);
+            for request in request_rx {
+                let response = writeln!(stdin, "{}", request)
+                    .and_then(|_| stdin.flush())
+                    .and_then(|_| {
+                        let mut line = String::new();
+                        stdout.read_line(&mut line).map(|_| line)
+                    });
+                if response_tx.send(response).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Ok(PluginProcess {
+            child,
+            requests,
+            responses,
+        })
+    }
+
+    fn round_trip(
+        &self,
+        process: &mut PluginProcess,
+        path: &Path,
+        code: &str,
+    ) -> TalosResult<PluginResponse> {
+        let path = path.to_string_lossy();
+        let request = serde_json::to_string(&PluginRequest { path: &path, code })?;
+        let stopped = || self.error(ErrorKind::Io, "plugin stopped unexpectedly".to_string());
+        process.requests.send(request).map_err(|_| stopped())?;
+
+        let line = match process.responses.recv_timeout(self.timeout) {
+            Ok(Ok(line)) => line,
+            Ok(Err(e)) => return Err(self.error(ErrorKind::Io, e.to_string())),
+            Err(RecvTimeoutError::Timeout) => {
+                return Err(self.error(
+                    ErrorKind::Io,
+                    format!(
+                        "no response within {} s; the plugin was stopped",
+                        self.timeout.as_secs_f64()
+                    ),
+                ))
+            }
+            Err(RecvTimeoutError::Disconnected) => return Err(stopped()),
+        };
+        if line.is_empty() {
+            return Err(self.error(
+                ErrorKind::Io,
+                "plugin exited without responding".to_string(),
+            ));
+        }
+
+        serde_json::from_str(&line)
+            .map_err(|e| self.error(ErrorKind::Parse, format!("invalid response: {}", e)))
+    }
+
+    fn error(&self, kind: ErrorKind, message: String) -> TalosError {
+        TalosError::Plugin {
+            name: self.name.clone(),
+            message,
+            kind,
+        }
+    }
+}
+
+impl LanguageExtractor for SubprocessExtractor {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn extract(&self, path: &Path, code: &str) -> TalosResult<Extraction> {
+        let mut guard = self
+            .process
+            .lock()
+            .map_err(|_| self.error(ErrorKind::Io, "plugin state poisoned".to_string()))?;
+
+        if guard.is_none() {
+            *guard = Some(self.spawn()?);
+        }
+        let Some(process) = guard.as_mut() else {
+            return Err(self.error(ErrorKind::Io, "plugin not running".to_string()));
+        };
+
+        match self.round_trip(process, path, code) {
+            Ok(PluginResponse {
+                error: Some(message),
+                ..
+            }) => Err(self.error(ErrorKind::Parse, message)),
+            Ok(response) => Ok(response.extraction),
+            Err(e) => {
+                // Start fresh next time rather than reuse a desynchronised or
+                // hung process; dropping it kills the child
+                *guard = None;
+                Err(e)
+            }
+        }
+    }
+}
+
+impl Drop for PluginProcess {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_program_is_an_io_error() {
+        let plugin = SubprocessExtractor::new("py", "/nonexistent/talos-plugin").unwrap();
+        let err = plugin.extract(Path::new("/tmp/a.py"), "x = 1").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Io);
+    }
+
+    #[test]
+    fn silent_plugin_times_out() {
+        let plugin = SubprocessExtractor::new("py", "sleep 30")
+            .unwrap()
+            .with_timeout(Duration::from_millis(200));
+        let err = plugin.extract(Path::new("/tmp/a.py"), "x = 1").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Io);
+        assert!(err.to_string().contains("no response within"), "{}", err);
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn garbage_response_is_a_parse_error() {
+        // GNU sed, unbuffered: answers every request line with `garbage`
+        let plugin = SubprocessExtractor::new("py", "sed -u s/.*/garbage/").unwrap();
+        let err = plugin.extract(Path::new("/tmp/a.py"), "x = 1").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Parse);
+    }
+}
@@ -0,0 +1,128 @@
+use super::SupportedLang;
+use crate::error::{TalosError, TalosResult};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use tree_sitter::Query;
+
+/// Query file names recognised in a `--queries-dir`, and the language each overrides
+const QUERY_FILES: &[(&str, SupportedLang)] = &[
+    ("javascript.scm", SupportedLang::JavaScript),
+    ("typescript.scm", SupportedLang::TypeScript),
+    ("tsx.scm", SupportedLang::TypeScriptReact),
+    ("css.scm", SupportedLang::CSS),
+    ("java.scm", SupportedLang::Java),
+    ("kotlin.scm", SupportedLang::Kotlin),
+    ("c.scm", SupportedLang::C),
+    ("cpp.scm", SupportedLang::Cpp),
+    ("csharp.scm", SupportedLang::CSharp),
+    ("php.scm", SupportedLang::Php),
+    ("ruby.scm", SupportedLang::Ruby),
+];
+
+/// User-supplied signature queries that replace the built-in ones, per language.
+///
+/// `typescript.scm` also applies to `.tsx` files unless a `tsx.scm` is present.
+#[derive(Debug, Default, Clone)]
+pub struct QueryOverrides {
+    queries: HashMap<SupportedLang, String>,
+}
+
+impl QueryOverrides {
+    /// Load and validate every `.scm` file in `dir` against its grammar
+    pub fn load(dir: &Path) -> TalosResult<Self> {
+        if !dir.is_dir() {
+            return Err(TalosError::InvalidInput(format!(
+                "Queries directory '{}' does not exist",
+                dir.display()
+            )));
+        }
+
+        let mut overrides = QueryOverrides::default();
+
+        let mut entries: Vec<_> = fs::read_dir(dir)?
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .map(|e| e.path())
+            .filter(|p| p.extension().is_some_and(|ext| ext == "scm"))
+            .collect();
+        entries.sort();
+        let has_tsx = entries
+            .iter()
+            .any(|p| p.file_name().is_some_and(|n| n == "tsx.scm"));
+
+        for path in entries {
+            let file_name = path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let lang = QUERY_FILES
+                .iter()
+                .find(|(name, _)| *name == file_name)
+                .map(|(_, lang)| *lang)
+                .ok_or_else(|| {
+                    let expected: Vec<&str> = QUERY_FILES.iter().map(|(name, _)| *name).collect();
+                    TalosError::InvalidInput(format!(
+                        "Unknown query file '{}' (expected one of: {})",
+                        path.display(),
+                        expected.join(", ")
+                    ))
+                })?;
+
+            let source = fs::read_to_string(&path)?;
+            Query::new(lang.grammar(), &source).map_err(|error| TalosError::TreeSitter {
+                error,
+                path: Some(path.clone()),
+            })?;
+
+            // TypeScript queries cover TSX unless it has its own file
+            if lang == SupportedLang::TypeScript && !has_tsx {
+                Query::new(SupportedLang::TypeScriptReact.grammar(), &source).map_err(|error| {
+                    TalosError::TreeSitter {
+                        error,
+                        path: Some(path.clone()),
+                    }
+                })?;
+                overrides
+                    .queries
+                    .insert(SupportedLang::TypeScriptReact, source.clone());
+            }
+            overrides.queries.insert(lang, source);
+        }
+
+        Ok(overrides)
+    }
+
+    pub fn get(&self, lang: SupportedLang) -> Option<&str> {
+        self.queries.get(&lang).map(String::as_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // `<T>x` casts only parse in the non-TSX grammar
+    const CAST_QUERY: &str = "(type_assertion) @signature";
+
+    #[test]
+    fn typescript_query_is_checked_against_tsx_without_a_tsx_file() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("typescript.scm"), CAST_QUERY).unwrap();
+        assert!(QueryOverrides::load(dir.path()).is_err());
+    }
+
+    #[test]
+    fn tsx_file_takes_over_tsx_validation() {
+        let dir = tempfile::tempdir().unwrap();
+        let tsx_query = "(jsx_element) @signature";
+        fs::write(dir.path().join("typescript.scm"), CAST_QUERY).unwrap();
+        fs::write(dir.path().join("tsx.scm"), tsx_query).unwrap();
+        let overrides = QueryOverrides::load(dir.path()).unwrap();
+        assert_eq!(overrides.get(SupportedLang::TypeScript), Some(CAST_QUERY));
+        assert_eq!(
+            overrides.get(SupportedLang::TypeScriptReact),
+            Some(tsx_query)
+        );
+    }
+}
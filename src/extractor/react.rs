@@ -0,0 +1,360 @@
+use crate::model::ComponentEntry;
+use std::collections::BTreeSet;
+use tree_sitter::Node;
+
+/// Base classes that make a class declaration a React component
+const COMPONENT_BASES: &[&str] = &[
+    "Component",
+    "PureComponent",
+    "React.Component",
+    "React.PureComponent",
+];
+
+const JSX_NODES: &[&str] = &["jsx_element", "jsx_self_closing_element", "jsx_fragment"];
+
+/// A detected component plus the signature line that replaces its plain function/class one
+pub(super) struct Component {
+    pub entry: ComponentEntry,
+    pub signature: String,
+}
+
+/// Find top-level (optionally exported) React components: PascalCase functions
+/// returning JSX, `forwardRef`/`memo` wrappers and `Component` subclasses.
+pub(super) fn detect_components(root: Node, code: &str) -> Vec<Component> {
+    let mut components = Vec::new();
+    let mut cursor = root.walk();
+
+    for statement in root.named_children(&mut cursor) {
+        let statement = unwrap_export(statement);
+        match statement.kind() {
+            "function_declaration" => {
+                components.extend(function_component(statement, code));
+            }
+            "class_declaration" => {
+                components.extend(class_component(statement, code));
+            }
+            "lexical_declaration" | "variable_declaration" => {
+                let mut decl_cursor = statement.walk();
+                for declarator in statement.named_children(&mut decl_cursor) {
+                    if declarator.kind() == "variable_declarator" {
+                        components.extend(variable_component(declarator, code));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    components
+}
+
+fn unwrap_export(node: Node) -> Node {
+    if node.kind() != "export_statement" {
+        return node;
+    }
+    node.child_by_field_name("declaration")
+        .or_else(|| node.child_by_field_name("value"))
+        .unwrap_or(node)
+}
+
+fn function_component(node: Node, code: &str) -> Option<Component> {
+    let name = component_name(node.child_by_field_name("name")?, code)?;
+    let body = node.child_by_field_name("body")?;
+    if !contains_jsx(body) {
+        return None;
+    }
+    Some(build_component(
+        name,
+        "function",
+        node,
+        first_param_type(node, code),
+        code,
+    ))
+}
+
+fn class_component(node: Node, code: &str) -> Option<Component> {
+    let name = component_name(node.child_by_field_name("name")?, code)?;
+    let heritage = child_of_kind(node, "class_heritage")?;
+
+    // TS wraps the base in an extends_clause with optional type arguments
+    let (base, props) = match child_of_kind(heritage, "extends_clause") {
+        Some(clause) => (
+            clause.child_by_field_name("value")?,
+            clause
+                .child_by_field_name("type_arguments")
+                .and_then(|args| nth_type_argument(args, 0, code)),
+        ),
+        None => (heritage.named_child(0)?, None),
+    };
+    if !COMPONENT_BASES.contains(&text(base, code)) {
+        return None;
+    }
+
+    let entry = ComponentEntry {
+        name: name.to_string(),
+        kind: "class".to_string(),
+        props,
+        hooks: Vec::new(),
+    };
+    Some(Component {
+        signature: format!("component {}", name),
+        entry,
+    })
+}
+
+fn variable_component(declarator: Node, code: &str) -> Option<Component> {
+    let name = component_name(declarator.child_by_field_name("name")?, code)?;
+    let value = declarator.child_by_field_name("value")?;
+
+    // `const Button: React.FC<ButtonProps> = ...`
+    let annotated_props = declarator
+        .child_by_field_name("type")
+        .and_then(|annotation| annotation.named_child(0))
+        .filter(|ty| ty.kind() == "generic_type")
+        .and_then(|ty| ty.child_by_field_name("type_arguments"))
+        .and_then(|args| nth_type_argument(args, 0, code));
+
+    match value.kind() {
+        "arrow_function" | "function_expression" | "function" => {
+            if !contains_jsx(value) {
+                return None;
+            }
+            let kind = if value.kind() == "arrow_function" {
+                "arrow"
+            } else {
+                "function"
+            };
+            let props = annotated_props.or_else(|| first_param_type(value, code));
+            Some(build_component(name, kind, value, props, code))
+        }
+        "call_expression" => {
+            let (kind, function, props) = unwrap_wrapper(value, code)?;
+            let props = props
+                .or(annotated_props)
+                .or_else(|| first_param_type(function, code));
+            Some(build_component(name, kind, function, props, code))
+        }
+        _ => None,
+    }
+}
+
+/// Unwrap `forwardRef(...)`, `memo(...)` and nestings like `memo(forwardRef(...))`,
+/// returning the outermost wrapper kind, the inner function and explicit props type
+fn unwrap_wrapper<'a>(
+    call: Node<'a>,
+    code: &str,
+) -> Option<(&'static str, Node<'a>, Option<String>)> {
+    let callee = text(call.child_by_field_name("function")?, code);
+    let (kind, props_index) = match callee {
+        "forwardRef" | "React.forwardRef" => ("forwardRef", 1),
+        "memo" | "React.memo" => ("memo", 0),
+        _ => return None,
+    };
+
+    let props = call
+        .child_by_field_name("type_arguments")
+        .and_then(|args| nth_type_argument(args, props_index, code));
+    let inner = call.child_by_field_name("arguments")?.named_child(0)?;
+
+    match inner.kind() {
+        "arrow_function" | "function_expression" | "function" => Some((kind, inner, props)),
+        "call_expression" => {
+            let (_, function, inner_props) = unwrap_wrapper(inner, code)?;
+            Some((kind, function, props.or(inner_props)))
+        }
+        _ => None,
+    }
+}
+
+fn build_component(
+    name: &str,
+    kind: &str,
+    function: Node,
+    props: Option<String>,
+    code: &str,
+) -> Component {
+    let params = function
+        .child_by_field_name("parameters")
+        .or_else(|| function.child_by_field_name("parameter"))
+        .map(|p| text(p, code).to_string())
+        .unwrap_or_default();
+    let params = if params.starts_with('(') {
+        params
+    } else {
+        format!("({})", params)
+    };
+
+    let mut hooks = BTreeSet::new();
+    if let Some(body) = function.child_by_field_name("body") {
+        collect_hooks(body, code, &mut hooks);
+    }
+
+    Component {
+        signature: format!("component {}{}", name, params),
+        entry: ComponentEntry {
+            name: name.to_string(),
+            kind: kind.to_string(),
+            props,
+            hooks: hooks.into_iter().collect(),
+        },
+    }
+}
+
+/// Type annotation of the first parameter, e.g. `ButtonProps` in `({ label }: ButtonProps)`
+fn first_param_type(function: Node, code: &str) -> Option<String> {
+    let params = function.child_by_field_name("parameters")?;
+    let first = params.named_child(0)?;
+    let annotation = first.child_by_field_name("type")?;
+    annotation
+        .named_child(0)
+        .map(|ty| text(ty, code).to_string())
+}
+
+fn nth_type_argument(args: Node, n: usize, code: &str) -> Option<String> {
+    args.named_child(n).map(|ty| text(ty, code).to_string())
+}
+
+/// Calls to `useX(...)` or `React.useX(...)` anywhere in the body
+fn collect_hooks(node: Node, code: &str, hooks: &mut BTreeSet<String>) {
+    if node.kind() == "call_expression" {
+        if let Some(function) = node.child_by_field_name("function") {
+            let callee = text(function, code);
+            let name = callee.strip_prefix("React.").unwrap_or(callee);
+            if is_hook_name(name) {
+                hooks.insert(name.to_string());
+            }
+        }
+    }
+
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        collect_hooks(child, code, hooks);
+    }
+}
+
+fn is_hook_name(name: &str) -> bool {
+    name.strip_prefix("use")
+        .and_then(|rest| rest.chars().next())
+        .is_some_and(|c| c.is_ascii_uppercase())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn contains_jsx(node: Node) -> bool {
+    if JSX_NODES.contains(&node.kind()) {
+        return true;
+    }
+    let mut cursor = node.walk();
+    let found = node.named_children(&mut cursor).any(contains_jsx);
+    found
+}
+
+/// PascalCase identifiers only; ALL_CAPS constants are not components
+fn component_name<'a>(node: Node, code: &'a str) -> Option<&'a str> {
+    let name = text(node, code);
+    let starts_upper = name.chars().next().is_some_and(|c| c.is_ascii_uppercase());
+    let has_lower = name.chars().any(|c| c.is_ascii_lowercase());
+    (starts_upper && has_lower).then_some(name)
+}
+
+fn child_of_kind<'a>(node: Node<'a>, kind: &str) -> Option<Node<'a>> {
+    let mut cursor = node.walk();
+    let found = node.named_children(&mut cursor).find(|c| c.kind() == kind);
+    found
+}
+
+fn text<'a>(node: Node, code: &'a str) -> &'a str {
+    code.get(node.byte_range()).unwrap_or_default().trim()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::extractor::SupportedLang;
+    use tree_sitter::Parser;
+
+    fn components(code: &str) -> Vec<Component> {
+        let mut parser = Parser::new();
+        parser
+            .set_language(SupportedLang::TypeScriptReact.grammar())
+            .unwrap();
+        let tree = parser.parse(code, None).unwrap();
+        detect_components(tree.root_node(), code)
+    }
+
+    fn entry(component: &Component) -> (&str, &str, Option<&str>) {
+        let entry = &component.entry;
+        (&entry.name, &entry.kind, entry.props.as_deref())
+    }
+
+    #[test]
+    fn function_components_with_props_and_hooks() {
+        let code = r#"
+export function Button({ label }: ButtonProps) {
+  const [on, setOn] = useState(false);
+  React.useEffect(() => {}, []);
+  return <button>{label}</button>;
+}
+"#;
+        let found = components(code);
+        assert_eq!(found.len(), 1);
+        assert_eq!(
+            entry(&found[0]),
+            ("Button", "function", Some("ButtonProps"))
+        );
+        assert_eq!(found[0].entry.hooks, vec!["useEffect", "useState"]);
+        assert_eq!(
+            found[0].signature,
+            "component Button({ label }: ButtonProps)"
+        );
+    }
+
+    #[test]
+    fn arrow_components_take_props_from_the_annotation() {
+        let code = "const Card: React.FC<CardProps> = ({ title }) => <div>{title}</div>;\n";
+        let found = components(code);
+        assert_eq!(entry(&found[0]), ("Card", "arrow", Some("CardProps")));
+    }
+
+    #[test]
+    fn forward_ref_and_memo_wrappers() {
+        let code = r#"
+export const Input = forwardRef<HTMLInputElement, InputProps>((props, ref) => <input ref={ref} />);
+const Row = React.memo(function Row({ id }: RowProps) { return <tr key={id} />; });
+const Cell = memo(forwardRef((props: CellProps, ref) => <td ref={ref} />));
+"#;
+        let found = components(code);
+        let entries: Vec<_> = found.iter().map(entry).collect();
+        assert_eq!(
+            entries,
+            vec![
+                ("Input", "forwardRef", Some("InputProps")),
+                ("Row", "memo", Some("RowProps")),
+                ("Cell", "memo", Some("CellProps")),
+            ]
+        );
+    }
+
+    #[test]
+    fn class_components_take_props_from_the_type_arguments() {
+        let code = r#"
+export class Modal extends React.Component<ModalProps, ModalState> {
+  render() { return <div />; }
+}
+class Store extends EventEmitter {}
+"#;
+        let found = components(code);
+        assert_eq!(found.len(), 1);
+        assert_eq!(entry(&found[0]), ("Modal", "class", Some("ModalProps")));
+        assert_eq!(found[0].signature, "component Modal");
+    }
+
+    #[test]
+    fn only_pascal_case_functions_returning_jsx_count() {
+        let code = r#"
+function renderRow() { return <tr />; }
+const MAX_ROWS = () => <div />;
+function Helper() { return null; }
+"#;
+        assert!(components(code).is_empty());
+    }
+}
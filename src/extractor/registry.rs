@@ -0,0 +1,288 @@
+use super::detect::{
+    language_key, refine_by_content, sniff_interpreter, sniff_modeline, SNIFF_BYTES,
+};
+use super::encoding::{read_source, DecodedSource};
+use super::{BuiltinExtractor, Extraction, LanguageExtractor, QueryOverrides, SupportedLang};
+use crate::error::{TalosError, TalosResult};
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+/// Maps file extensions and exact file names to language extractors.
+///
+/// Exact file names (e.g. `Dockerfile`) take precedence over extensions, and a
+/// later registration for the same key replaces an earlier one. When reading a
+/// file, an editor modeline overrides both unless the user mapped the extension
+/// explicitly (a plugin or `--lang-map`), and files with neither a known name
+/// nor extension are identified by their `#!` line.
+#[derive(Default)]
+pub struct ExtractorRegistry {
+    extractors: Vec<Box<dyn LanguageExtractor>>,
+    by_extension: HashMap<String, usize>,
+    by_file_name: HashMap<String, usize>,
+    /// Extensions routed by [`register`](Self::register) or
+    /// [`map_extension`](Self::map_extension) rather than the built-ins
+    explicit: HashSet<String>,
+}
+
+/// Extractor chosen for a file, and whether its parse is only best-effort
+struct Detection {
+    idx: usize,
+    /// Parsed with a stand-in grammar (Flow as TSX), so syntax errors are expected
+    best_effort: bool,
+}
+
+impl ExtractorRegistry {
+    /// Empty registry with no languages
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registry with the built-in tree-sitter languages, applying any query overrides
+    pub fn with_builtins(overrides: &QueryOverrides) -> Self {
+        let mut registry = Self::new();
+        for &lang in SupportedLang::ALL {
+            let extractor = BuiltinExtractor {
+                lang,
+                query: overrides.get(lang).map(String::from),
+            };
+            registry.add(Box::new(extractor), lang.extensions(), lang.file_names());
+        }
+        registry
+    }
+
+    /// Register `extractor` for the given extensions (without dot) and exact file
+    /// names. These mappings are explicit: modelines and content sniffing don't
+    /// override them.
+    pub fn register(
+        &mut self,
+        extractor: Box<dyn LanguageExtractor>,
+        extensions: &[&str],
+        file_names: &[&str],
+    ) {
+        self.add(extractor, extensions, file_names);
+        self.explicit
+            .extend(extensions.iter().map(|ext| normalize_extension(ext)));
+    }
+
+    fn add(
+        &mut self,
+        extractor: Box<dyn LanguageExtractor>,
+        extensions: &[&str],
+        file_names: &[&str],
+    ) {
+        let idx = self.extractors.len();
+        self.extractors.push(extractor);
+
+        for ext in extensions {
+            self.by_extension.insert(normalize_extension(ext), idx);
+        }
+        for name in file_names {
+            self.by_file_name.insert(name.to_string(), idx);
+        }
+    }
+
+    /// Route extension `from` to whatever handles `to`, which may be an extension
+    /// (`js`) or a language name (`javascript`), e.g. `--lang-map mjs=js`
+    pub fn map_extension(&mut self, from: &str, to: &str) -> TalosResult<()> {
+        let key = language_key(to);
+        let idx = *self
+            .by_extension
+            .get(&key)
+            .ok_or_else(|| TalosError::InvalidInput(format!("No extractor handles '{}'", to)))?;
+        let from = normalize_extension(from);
+        self.by_extension.insert(from.clone(), idx);
+        self.explicit.insert(from);
+        Ok(())
+    }
+
+    /// Extractor responsible for `path` by file name or extension, if any
+    pub fn lookup(&self, path: &Path) -> Option<&dyn LanguageExtractor> {
+        self.lookup_index(path)
+            .map(|idx| self.extractors[idx].as_ref())
+    }
+
+    fn lookup_index(&self, path: &Path) -> Option<usize> {
+        let by_name = path
+            .file_name()
+            .and_then(|name| self.by_file_name.get(name.to_string_lossy().as_ref()));
+        let idx = by_name.or_else(|| self.by_extension.get(&extension_of(path)?))?;
+        Some(*idx)
+    }
+
+    /// Extractor for `path` given its content: an explicit extension mapping
+    /// wins, then a modeline, then file name, then extension (refined by content,
+    /// e.g. Flow-annotated `.js` as TSX), then the `#!` interpreter
+    fn detect_index(&self, path: &Path, code: &str) -> Option<Detection> {
+        let known = |key: String| self.by_extension.get(&key).copied();
+        let sure = |idx| Detection {
+            idx,
+            best_effort: false,
+        };
+
+        let ext = extension_of(path).filter(|_| !self.handles_file_name(path));
+        if let Some(ext) = ext.as_ref().filter(|ext| self.explicit.contains(*ext)) {
+            return known(ext.clone()).map(sure);
+        }
+        if let Some(idx) = sniff_modeline(code).and_then(known) {
+            return Some(sure(idx));
+        }
+        if let Some(ext) = ext {
+            if let Some(idx) = refine_by_content(&ext, code).and_then(|k| known(k.to_string())) {
+                return Some(Detection {
+                    idx,
+                    best_effort: true,
+                });
+            }
+        }
+        self.lookup_index(path)
+            .or_else(|| sniff_interpreter(code).and_then(known))
+            .map(sure)
+    }
+
+    /// Extractor that would handle `path` with content `code`
+    pub fn detect(&self, path: &Path, code: &str) -> Option<&dyn LanguageExtractor> {
+        self.detect_index(path, code)
+            .map(|detection| self.extractors[detection.idx].as_ref())
+    }
+
+    /// Registry key for an extensionless file, sniffed from its first bytes,
+    /// e.g. `js` for a `#!/usr/bin/env node` script
+    pub fn sniff_file(&self, path: &Path) -> Option<String> {
+        if path.extension().is_some() || self.handles_file_name(path) {
+            return None;
+        }
+
+        let mut head = Vec::with_capacity(SNIFF_BYTES);
+        File::open(path)
+            .ok()?
+            .take(SNIFF_BYTES as u64)
+            .read_to_end(&mut head)
+            .ok()?;
+        let head = String::from_utf8_lossy(&head);
+
+        sniff_interpreter(&head)
+            .or_else(|| sniff_modeline(&head))
+            .filter(|key| self.by_extension.contains_key(key))
+    }
+
+    /// True if `path` is matched by an exact file name registration
+    pub fn handles_file_name(&self, path: &Path) -> bool {
+        path.file_name().is_some_and(|name| {
+            self.by_file_name
+                .contains_key(name.to_string_lossy().as_ref())
+        })
+    }
+
+    /// Extensions served by the extractor registered for `path`'s exact file
+    /// name, e.g. `rb` for `Gemfile`; empty when the name isn't registered
+    pub fn file_name_extensions(&self, path: &Path) -> Vec<&str> {
+        let Some(idx) = path
+            .file_name()
+            .and_then(|name| self.by_file_name.get(name.to_string_lossy().as_ref()))
+        else {
+            return Vec::new();
+        };
+        self.by_extension
+            .iter()
+            .filter(|(_, ext_idx)| *ext_idx == idx)
+            .map(|(ext, _)| ext.as_str())
+            .collect()
+    }
+
+    /// Registered extensions, sorted
+    pub fn extensions(&self) -> Vec<String> {
+        let mut exts: Vec<String> = self.by_extension.keys().cloned().collect();
+        exts.sort();
+        exts
+    }
+
+    /// Read `path` and run its extractor; files with no extractor yield an empty result
+    pub fn extract_file(&self, path: &Path) -> TalosResult<Extraction> {
+        self.extract_source(path, read_source(path)?)
+    }
+
+    /// Extract from source already read with [`read_source`], e.g. after inspecting it
+    pub fn extract_source(&self, path: &Path, source: DecodedSource) -> TalosResult<Extraction> {
+        let Some(detection) = self.detect_index(path, &source.code) else {
+            return Ok(Extraction::default());
+        };
+
+        let mut extraction = self.extractors[detection.idx].extract(path, &source.code)?;
+        if detection.best_effort {
+            // Flow-only syntax the TSX grammar can't parse isn't the file's fault
+            extraction.diagnostics.clear();
+        }
+        extraction.encoding = source.encoding;
+        extraction.diagnostics.splice(0..0, source.lossy);
+        Ok(extraction)
+    }
+}
+
+fn normalize_extension(ext: &str) -> String {
+    ext.trim().trim_start_matches('.').to_ascii_lowercase()
+}
+
+fn extension_of(path: &Path) -> Option<String> {
+    path.extension()
+        .map(|ext| ext.to_string_lossy().to_ascii_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source(code: &str) -> DecodedSource {
+        DecodedSource {
+            code: code.to_string(),
+            encoding: None,
+            lossy: None,
+        }
+    }
+
+    fn detected(registry: &ExtractorRegistry, path: &str, code: &str) -> Option<String> {
+        registry
+            .detect(Path::new(path), code)
+            .map(|extractor| extractor.name().to_string())
+    }
+
+    #[test]
+    fn flow_only_syntax_is_parsed_best_effort() {
+        let registry = ExtractorRegistry::with_builtins(&Default::default());
+        let code = "// @flow\n\
+            import typeof Theme from './theme';\n\
+            opaque type Id = string;\n\
+            type Exact = {| id: ?Id |};\n\
+            export function load(id: ?Id): Exact { return { id }; }\n\
+            export class Store {}\n";
+
+        let extraction = registry
+            .extract_source(Path::new("store.js"), source(code))
+            .unwrap();
+        assert!(extraction.diagnostics.is_empty());
+        let tsx = registry
+            .extract_source(Path::new("store.tsx"), source(code))
+            .unwrap();
+        assert!(!tsx.diagnostics.is_empty());
+        assert!(extraction
+            .signatures
+            .iter()
+            .any(|s| s.contains("class Store")));
+    }
+
+    #[test]
+    fn explicit_mappings_win_over_modelines() {
+        let mut registry = ExtractorRegistry::with_builtins(&Default::default());
+        let code = "// vim: set ft=ruby:\nfunction a() {}\n";
+        assert_eq!(detected(&registry, "a.inc", code), Some("ruby".into()));
+
+        registry.map_extension("inc", "js").unwrap();
+        assert_eq!(
+            detected(&registry, "a.inc", code),
+            Some("javascript".into())
+        );
+        // Built-in extensions still defer to the modeline
+        assert_eq!(detected(&registry, "a.ts", code), Some("ruby".into()));
+    }
+}
@@ -0,0 +1,201 @@
+use super::{
+    collect_definitions, collect_imports, custom_signature, parse_diagnostics, Extraction,
+    SupportedLang,
+};
+use crate::error::{TalosError, TalosResult};
+use std::collections::BTreeSet;
+use tree_sitter::{Node, Parser, Query, QueryCapture, QueryCursor};
+
+const RUBY_QUERY: &str = include_str!("../../queries/ruby.scm");
+const RUBY_IMPORTS_QUERY: &str = include_str!("../../queries/ruby_imports.scm");
+const RUBY_DEFINITIONS_QUERY: &str = include_str!("../../queries/ruby_definitions.scm");
+
+pub fn extract_ruby(code: &str, query: Option<&str>) -> TalosResult<Extraction> {
+    let lang = SupportedLang::Ruby.grammar();
+    let mut parser = Parser::new();
+    parser
+        .set_language(lang)
+        .map_err(|e| TalosError::ScanError(format!("Failed to set parser language: {}", e)))?;
+
+    let tree = parser
+        .parse(code, None)
+        .ok_or_else(|| TalosError::ScanError("Failed to parse code".to_string()))?;
+
+    let query = Query::new(lang, query.unwrap_or(RUBY_QUERY))?;
+    let mut cursor = QueryCursor::new();
+    let mut signatures: BTreeSet<String> = BTreeSet::new();
+
+    for query_match in cursor.matches(&query, tree.root_node(), code.as_bytes()) {
+        if let Some(signature) = create_ruby_signature(&query, query_match.captures, code) {
+            signatures.insert(signature);
+        }
+    }
+
+    let imports_query = Query::new(lang, RUBY_IMPORTS_QUERY)?;
+    let imports = collect_imports(&imports_query, tree.root_node(), code);
+    let definitions_query = Query::new(lang, RUBY_DEFINITIONS_QUERY)?;
+    let definitions = collect_definitions(&definitions_query, tree.root_node(), code);
+
+    Ok(Extraction {
+        signatures: signatures.into_iter().collect(),
+        imports,
+        components: Vec::new(),
+        definitions,
+        diagnostics: parse_diagnostics(tree.root_node(), code),
+        encoding: None,
+    })
+}
+
+#[derive(Debug)]
+enum RubySignatureType {
+    Module(String),
+    Class(String, Option<String>),
+    Method(String, String),
+    Attribute(String),
+}
+
+impl RubySignatureType {
+    fn render(self) -> String {
+        match self {
+            Self::Module(name) => format!("module {}", name),
+            Self::Class(name, Some(superclass)) => format!("class {} < {}", name, superclass),
+            Self::Class(name, None) => format!("class {}", name),
+            Self::Method(name, params) => format!("def {}{}", name, params),
+            Self::Attribute(call) => call,
+        }
+    }
+}
+
+struct RubyCaptureMap<'a> {
+    query: &'a Query,
+    captures: &'a [QueryCapture<'a>],
+}
+
+impl<'a> RubyCaptureMap<'a> {
+    fn new(query: &'a Query, captures: &'a [QueryCapture<'a>]) -> Self {
+        Self { query, captures }
+    }
+
+    fn get_node(&self, name: &str) -> Option<Node<'a>> {
+        self.captures
+            .iter()
+            .find(|c| self.query.capture_names()[c.index as usize] == name)
+            .map(|c| c.node)
+    }
+}
+
+fn create_ruby_signature(query: &Query, captures: &[QueryCapture], code: &str) -> Option<String> {
+    let capture_map = RubyCaptureMap::new(query, captures);
+    let Some(signature_type) = detect_ruby_signature_type(&capture_map, code) else {
+        return custom_signature(query, captures, code);
+    };
+    Some(signature_type.render())
+}
+
+fn detect_ruby_signature_type(captures: &RubyCaptureMap, code: &str) -> Option<RubySignatureType> {
+    // module Billing
+    if let Some(node) = captures.get_node("module") {
+        let name = text(node.child_by_field_name("name")?, code);
+        return Some(RubySignatureType::Module(name));
+    }
+
+    // class Invoice < Base
+    if let Some(node) = captures.get_node("class") {
+        let name = text(node.child_by_field_name("name")?, code);
+        let superclass = node
+            .child_by_field_name("superclass")
+            .and_then(|s| s.named_child(0))
+            .map(|s| text(s, code));
+        return Some(RubySignatureType::Class(name, superclass));
+    }
+
+    // def pay!(amount:, **opts) / def self.find(id)
+    if let Some(node) = captures.get_node("method") {
+        let name = text(node.child_by_field_name("name")?, code);
+        let name = match node.child_by_field_name("object") {
+            Some(object) => format!("{}.{}", text(object, code), name),
+            None => name,
+        };
+        let params = node
+            .child_by_field_name("parameters")
+            .map(|p| text(p, code))
+            .unwrap_or_default();
+        let params = if params.is_empty() || params.starts_with('(') {
+            params
+        } else {
+            format!("({})", params)
+        };
+        return Some(RubySignatureType::Method(name, params));
+    }
+
+    // attr_accessor :total, :id
+    if let Some(node) = captures.get_node("attribute") {
+        return Some(RubySignatureType::Attribute(text(node, code)));
+    }
+
+    None
+}
+
+/// Node text with whitespace collapsed
+fn text(node: Node, code: &str) -> String {
+    code.get(node.byte_range())
+        .unwrap_or_default()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::extractor::{ExtractorRegistry, QueryOverrides};
+    use std::path::Path;
+
+    #[test]
+    fn classes_methods_and_accessors() {
+        let code = r#"
+require 'json'
+require_relative 'base'
+module Shop
+  class User < Base
+    attr_accessor :name, :email
+    def self.find(id)
+    end
+  end
+end
+"#;
+        let extraction = extract_ruby(code, None).unwrap();
+        for expected in [
+            "module Shop",
+            "class User < Base",
+            "attr_accessor :name, :email",
+            "def self.find(id)",
+        ] {
+            assert!(
+                extraction.signatures.contains(&expected.to_string()),
+                "missing {expected:?} in {:?}",
+                extraction.signatures
+            );
+        }
+        let mut imports = extraction.imports.clone();
+        imports.sort();
+        assert_eq!(imports, vec!["base", "json"]);
+    }
+
+    #[test]
+    fn rakefile_and_gemfile_are_ruby() {
+        let registry = ExtractorRegistry::with_builtins(&QueryOverrides::default());
+        for name in ["Rakefile", "Gemfile", "lib/tasks/Rakefile"] {
+            assert!(registry.handles_file_name(Path::new(name)), "{name}");
+            let mut exts = registry.file_name_extensions(Path::new(name));
+            exts.sort();
+            assert_eq!(exts, vec!["gemspec", "rake", "rb"]);
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        let rakefile = dir.path().join("Rakefile");
+        std::fs::write(&rakefile, "def helper(x)\nend\ntask :default\n").unwrap();
+        let extraction = registry.extract_file(&rakefile).unwrap();
+        assert_eq!(extraction.signatures, vec!["def helper(x)"]);
+    }
+}
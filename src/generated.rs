@@ -0,0 +1,392 @@
+use crate::error::{TalosError, TalosResult};
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::Match;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// File names of minified bundles, protobuf/gRPC stubs and other codegen output
+const GENERATED_NAMES: &[&str] = &[
+    "*.min.js",
+    "*.min.mjs",
+    "*.min.css",
+    "*-min.js",
+    "*.bundle.js",
+    "*.chunk.js",
+    "*.generated.*",
+    "*_generated.*",
+    "*.g.cs",
+    "*.designer.cs",
+    "*_pb.js",
+    "*_pb.d.ts",
+    "*_grpc_pb.js",
+    "*_grpc_pb.d.ts",
+    "*_pb2.py",
+    "*.pb.h",
+    "*.pb.cc",
+];
+
+/// Lowercase phrases that mark a file as generated when they appear in its
+/// leading comments
+const HEADER_MARKERS: &[&str] = &[
+    "@generated",
+    "<auto-generated",
+    "this file is auto-generated",
+    "this file is autogenerated",
+    "this file is automatically generated",
+    "this file was auto-generated",
+    "this file was autogenerated",
+    "this file was automatically generated",
+    "this file is generated",
+    "this file was generated",
+    "generated by the protocol buffer compiler",
+];
+
+/// Lines searched for [`HEADER_MARKERS`]
+const HEADER_LINES: usize = 10;
+
+/// Files at least this large whose average line is longer than
+/// [`MINIFIED_AVG_LINE`] characters are treated as minified
+const MINIFIED_MIN_BYTES: usize = 512;
+const MINIFIED_AVG_LINE: usize = 110;
+
+const ATTRIBUTE: &str = "linguist-generated";
+
+/// Recognises generated and minified sources. `.gitattributes` files are
+/// loaded once per directory, from the repository (or scan) root down.
+pub struct GeneratedDetector {
+    top: PathBuf,
+    names: GlobSet,
+    attributes: HashMap<PathBuf, Option<Gitignore>>,
+}
+
+impl GeneratedDetector {
+    pub fn new(root: &Path) -> TalosResult<Self> {
+        let mut builder = GlobSetBuilder::new();
+        for pattern in GENERATED_NAMES {
+            let glob = GlobBuilder::new(pattern)
+                .case_insensitive(true)
+                .build()
+                .map_err(|e| {
+                    TalosError::ScanError(format!("Invalid generated file pattern: {}", e))
+                })?;
+            builder.add(glob);
+        }
+        let names = builder.build().map_err(|e| {
+            TalosError::ScanError(format!("Failed to build generated file globset: {}", e))
+        })?;
+
+        let top = root
+            .ancestors()
+            .find(|dir| dir.join(".git").exists())
+            .unwrap_or(root)
+            .to_path_buf();
+
+        Ok(Self {
+            top,
+            names,
+            attributes: HashMap::new(),
+        })
+    }
+
+    /// Why `path` looks generated, or `None`. A `linguist-generated` attribute
+    /// decides either way; otherwise the file name, header comments and line
+    /// length are checked in that order.
+    pub fn detect(&mut self, path: &Path, code: &str) -> Option<String> {
+        match self.attribute(path) {
+            Some((true, source)) => return Some(format!("{} sets `{}`", source, ATTRIBUTE)),
+            Some((false, _)) => return None,
+            None => {}
+        }
+        self.name_marker(path)
+            .or_else(|| header_marker(code))
+            .or_else(|| minified(code))
+    }
+
+    fn name_marker(&self, path: &Path) -> Option<String> {
+        let name = path.file_name()?;
+        let idx = *self.names.matches(name).first()?;
+        Some(format!("file name matches `{}`", GENERATED_NAMES[idx]))
+    }
+
+    /// The `linguist-generated` setting for `path` from the deepest
+    /// `.gitattributes` that mentions it, with that file's relative path
+    fn attribute(&mut self, path: &Path) -> Option<(bool, String)> {
+        let mut dirs: Vec<PathBuf> = path
+            .parent()?
+            .ancestors()
+            .take_while(|dir| dir.starts_with(&self.top))
+            .map(Path::to_path_buf)
+            .collect();
+        dirs.reverse();
+
+        let mut verdict = None;
+        for dir in dirs {
+            let Some(matcher) = self
+                .attributes
+                .entry(dir.clone())
+                .or_insert_with(|| load_attributes(&dir))
+            else {
+                continue;
+            };
+            let generated = match matcher.matched(path, false) {
+                Match::Ignore(_) => true,
+                Match::Whitelist(_) => false,
+                Match::None => continue,
+            };
+            let source = dir.join(".gitattributes");
+            let source = source.strip_prefix(&self.top).unwrap_or(&source);
+            verdict = Some((generated, source.to_string_lossy().to_string()));
+        }
+        verdict
+    }
+}
+
+/// `.gitattributes` lines that set or unset `linguist-generated`, as a gitignore
+/// matcher: set patterns "ignore", unset patterns are whitelisted
+fn load_attributes(dir: &Path) -> Option<Gitignore> {
+    let content = std::fs::read_to_string(dir.join(".gitattributes")).ok()?;
+    let mut builder = GitignoreBuilder::new(dir);
+    let mut any = false;
+
+    for line in content.lines() {
+        let mut fields = line.split_whitespace();
+        let Some(pattern) = fields.next() else {
+            continue;
+        };
+        // Negative patterns are not allowed in .gitattributes
+        if pattern.starts_with('#') || pattern.starts_with('!') {
+            continue;
+        }
+        let setting = fields.rev().find_map(|attr| match attr {
+            "linguist-generated" | "linguist-generated=true" => Some(true),
+            "-linguist-generated" | "!linguist-generated" | "linguist-generated=false" => {
+                Some(false)
+            }
+            _ => None,
+        });
+        let rule = match setting {
+            Some(true) => pattern.to_string(),
+            Some(false) => format!("!{}", pattern),
+            None => continue,
+        };
+        if builder.add_line(None, &rule).is_ok() {
+            any = true;
+        }
+    }
+
+    if !any {
+        return None;
+    }
+    builder.build().ok()
+}
+
+/// `// Code generated by protoc-gen-go. DO NOT EDIT.`, `/** @generated */`, ...
+/// Only comments before the first line of code count, so string literals and
+/// prose further down never mark a file as generated.
+fn header_marker(code: &str) -> Option<String> {
+    if let Some(line) = webpack_runtime(code) {
+        return Some(format!("`webpackBootstrap` runtime on line {}", line));
+    }
+    for (line, text) in leading_comments(code) {
+        let lower = text.to_ascii_lowercase();
+        // Go's convention: `Code generated <by whom>. DO NOT EDIT.`
+        let go_header =
+            lower.trim_start().starts_with("code generated ") && lower.contains("do not edit");
+        if go_header {
+            return Some(format!(
+                "`Code generated ... DO NOT EDIT` header on line {}",
+                line
+            ));
+        }
+        if let Some((start, marker)) = HEADER_MARKERS
+            .iter()
+            .find_map(|marker| lower.find(marker).map(|start| (start, marker)))
+        {
+            return Some(format!(
+                "`{}` marker on line {}",
+                &text[start..start + marker.len()],
+                line
+            ));
+        }
+    }
+    None
+}
+
+/// The text of each comment line (1-based) at the top of `code`, up to
+/// [`HEADER_LINES`]. A `#!` line and a PHP open tag may come first; the first
+/// line with code ends the header.
+fn leading_comments(code: &str) -> Vec<(usize, &str)> {
+    let mut comments = Vec::new();
+    let mut in_block = false;
+    for (idx, line) in code.lines().take(HEADER_LINES).enumerate() {
+        let trimmed = line.trim();
+        if in_block {
+            match trimmed.find("*/") {
+                Some(end) => {
+                    in_block = false;
+                    comments.push((idx + 1, &trimmed[..end]));
+                    if !trimmed[end + 2..].trim().is_empty() {
+                        break;
+                    }
+                }
+                None => comments.push((idx + 1, trimmed)),
+            }
+            continue;
+        }
+        if trimmed.is_empty() || (idx == 0 && trimmed.starts_with("#!")) || trimmed == "<?php" {
+            continue;
+        }
+        if let Some(text) = trimmed.strip_prefix("//") {
+            comments.push((idx + 1, text));
+        } else if let Some(text) = trimmed.strip_prefix("/*") {
+            match text.find("*/") {
+                Some(end) => {
+                    comments.push((idx + 1, &text[..end]));
+                    if !text[end + 2..].trim().is_empty() {
+                        break;
+                    }
+                }
+                None => {
+                    in_block = true;
+                    comments.push((idx + 1, text));
+                }
+            }
+        } else if let Some(text) = trimmed.strip_prefix('#') {
+            // `#include`, `#define`, ... are code
+            if text.starts_with(|c: char| c.is_ascii_alphabetic()) {
+                break;
+            }
+            comments.push((idx + 1, text));
+        } else {
+            break;
+        }
+    }
+    comments
+}
+
+/// Webpack's development bundles open with `/******/` runtime lines tagged
+/// `// webpackBootstrap`
+fn webpack_runtime(code: &str) -> Option<usize> {
+    let lines: Vec<&str> = code.lines().take(HEADER_LINES).collect();
+    let first = lines.iter().find(|line| !line.trim().is_empty())?;
+    if !first.trim_start().starts_with("/******/") {
+        return None;
+    }
+    lines
+        .iter()
+        .position(|line| line.contains("webpackBootstrap"))
+        .map(|idx| idx + 1)
+}
+
+fn minified(code: &str) -> Option<String> {
+    let lines = code.lines().count();
+    if code.len() < MINIFIED_MIN_BYTES || lines == 0 {
+        return None;
+    }
+    let average = code.len() / lines;
+    (average > MINIFIED_AVG_LINE).then(|| {
+        format!(
+            "looks minified (average line length {} characters)",
+            average
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn prose_in_a_leading_comment_is_not_a_marker() {
+        let code = "// User model. IDs are auto-generated by the database.\nexport interface User { id: string }\n";
+        assert_eq!(header_marker(code), None);
+    }
+
+    #[test]
+    fn string_literals_are_not_markers() {
+        let code = "const WARNING = \"Do not edit this record...\";\n// @generated\n";
+        assert_eq!(header_marker(code), None);
+    }
+
+    #[test]
+    fn go_style_header() {
+        let code = "// Code generated by protoc-gen-go. DO NOT EDIT.\n\npackage api\n";
+        assert_eq!(
+            header_marker(code).as_deref(),
+            Some("`Code generated ... DO NOT EDIT` header on line 1")
+        );
+    }
+
+    #[test]
+    fn markers_inside_block_comments() {
+        let code = "#!/usr/bin/env node\n/**\n * @generated\n */\nmodule.exports = {};\n";
+        assert_eq!(
+            header_marker(code).as_deref(),
+            Some("`@generated` marker on line 3")
+        );
+
+        let code = "<?php\n/*\n * This file is auto-generated. */\nclass A {}\n";
+        assert_eq!(
+            header_marker(code).as_deref(),
+            Some("`This file is auto-generated` marker on line 3")
+        );
+    }
+
+    #[test]
+    fn hash_comments_and_auto_generated_tags() {
+        let code = "# frozen_string_literal: true\n# This file was automatically generated\n";
+        assert!(header_marker(code).is_some());
+
+        let code = "// <auto-generated />\nnamespace App {}\n";
+        assert!(header_marker(code).is_some());
+
+        let code = "#include <stdio.h>\n// @generated\n";
+        assert_eq!(header_marker(code), None);
+    }
+
+    #[test]
+    fn webpack_bootstrap() {
+        let code = "/******/ (function(modules) { // webpackBootstrap\n/******/ })\n";
+        assert_eq!(
+            header_marker(code).as_deref(),
+            Some("`webpackBootstrap` runtime on line 1")
+        );
+        assert_eq!(header_marker("// webpackBootstrap is a runtime\n"), None);
+    }
+
+    #[test]
+    fn minified_code() {
+        let line = format!("var a={};", "1".repeat(600));
+        assert!(minified(&line).is_some());
+        assert_eq!(minified(&"let a = 1;\n".repeat(100)), None);
+    }
+
+    #[test]
+    fn detects_by_name_and_gitattributes() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join(".git")).unwrap();
+        fs::write(
+            dir.path().join(".gitattributes"),
+            "gen/** linguist-generated\ngen/keep.js -linguist-generated\n",
+        )
+        .unwrap();
+        let mut detector = GeneratedDetector::new(dir.path()).unwrap();
+        let code = "export const a = 1;\n";
+
+        assert_eq!(
+            detector.detect(&dir.path().join("vendor/jquery.min.js"), code),
+            Some("file name matches `*.min.js`".to_string())
+        );
+        assert_eq!(
+            detector.detect(&dir.path().join("gen/api.js"), code),
+            Some(".gitattributes sets `linguist-generated`".to_string())
+        );
+        assert_eq!(
+            detector.detect(&dir.path().join("gen/keep.js"), "// @generated\n"),
+            None
+        );
+        assert_eq!(detector.detect(&dir.path().join("src/app.js"), code), None);
+    }
+}
@@ -0,0 +1,87 @@
+use crate::model::{DependencyEdge, Document, FileEntry};
+use crate::resolver::ImportResolver;
+use crate::types::GraphFormat;
+use std::collections::BTreeSet;
+
+/// Resolve one file's imports, returning its outgoing edges (sorted, deduplicated)
+pub fn link_file(file: &mut FileEntry, resolver: &ImportResolver) -> Vec<DependencyEdge> {
+    let mut targets: BTreeSet<String> = BTreeSet::new();
+
+    for import in file.imports.iter_mut() {
+        import.resolved_path = resolver.resolve(&file.relative_file_path, &import.specifier);
+
+        if let Some(target) = &import.resolved_path {
+            if target != &file.relative_file_path {
+                targets.insert(target.clone());
+            }
+        }
+    }
+
+    targets
+        .into_iter()
+        .map(|to| DependencyEdge {
+            root: None,
+            from: file.relative_file_path.clone(),
+            to,
+        })
+        .collect()
+}
+
+/// Render the document's dependency graph as DOT or Mermaid
+pub fn render_graph(doc: &Document, format: GraphFormat) -> String {
+    let nodes = graph_nodes(doc);
+    match format {
+        GraphFormat::Dot => render_dot(&nodes, &doc.dependencies),
+        GraphFormat::Mermaid => render_mermaid(&nodes, &doc.dependencies),
+    }
+}
+
+fn graph_nodes(doc: &Document) -> BTreeSet<&str> {
+    let mut nodes: BTreeSet<&str> = doc.files().map(|f| f.relative_file_path.as_str()).collect();
+    for edge in &doc.dependencies {
+        nodes.insert(edge.from.as_str());
+        nodes.insert(edge.to.as_str());
+    }
+    nodes
+}
+
+fn render_dot(nodes: &BTreeSet<&str>, edges: &[DependencyEdge]) -> String {
+    let mut out = String::from("digraph talos {\n  rankdir=LR;\n  node [shape=box];\n");
+    for node in nodes {
+        out.push_str(&format!("  \"{}\";\n", escape_dot(node)));
+    }
+    for edge in edges {
+        out.push_str(&format!(
+            "  \"{}\" -> \"{}\";\n",
+            escape_dot(&edge.from),
+            escape_dot(&edge.to)
+        ));
+    }
+    out.push_str("}\n");
+    out
+}
+
+fn render_mermaid(nodes: &BTreeSet<&str>, edges: &[DependencyEdge]) -> String {
+    // Mermaid ids must be simple identifiers, so number the nodes and label them
+    let ids: Vec<&str> = nodes.iter().copied().collect();
+    let id_of = |path: &str| ids.binary_search(&path).map(|i| format!("n{}", i));
+
+    let mut out = String::from("graph LR\n");
+    for (i, node) in ids.iter().enumerate() {
+        out.push_str(&format!("  n{}[\"{}\"]\n", i, escape_mermaid(node)));
+    }
+    for edge in edges {
+        if let (Ok(from), Ok(to)) = (id_of(&edge.from), id_of(&edge.to)) {
+            out.push_str(&format!("  {} --> {}\n", from, to));
+        }
+    }
+    out
+}
+
+fn escape_dot(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn escape_mermaid(s: &str) -> String {
+    s.replace('"', "#quot;")
+}
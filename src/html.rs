@@ -0,0 +1,346 @@
+use crate::error::TalosResult;
+use crate::model::{Document, FileEntry};
+use crate::writer::DocumentWriter;
+use std::collections::BTreeMap;
+
+/// Inline so the report works offline and as a single attachment
+const STYLE: &str = r#"
+body { font: 14px/1.45 system-ui, sans-serif; margin: 0; color: #1f2328; background: #fff; }
+header { position: sticky; top: 0; background: #f6f8fa; border-bottom: 1px solid #d0d7de; padding: 12px 24px; }
+header h1 { font-size: 18px; margin: 0 0 4px; }
+header p { margin: 0 0 8px; color: #59636e; }
+#search { width: 100%; max-width: 480px; padding: 6px 10px; font-size: 14px; border: 1px solid #d0d7de; border-radius: 6px; }
+#count { margin-left: 12px; color: #59636e; }
+main { padding: 12px 24px 48px; }
+h2 { font-size: 16px; border-bottom: 1px solid #d0d7de; padding-bottom: 4px; margin-top: 28px; }
+details { margin: 2px 0; }
+details details { margin-left: 18px; }
+summary { cursor: pointer; }
+.dir > summary { font-weight: 600; }
+.file > summary { font-family: ui-monospace, monospace; }
+.meta { color: #59636e; font-weight: normal; font-family: system-ui, sans-serif; font-size: 12px; margin-left: 6px; }
+.tag { display: inline-block; font-size: 11px; padding: 0 6px; border-radius: 10px; background: #ddf4ff; color: #0969da; margin-left: 6px; }
+.tag.warn { background: #fff8c5; color: #9a6700; }
+ul.signatures, ul.imports { margin: 4px 0 8px 18px; padding-left: 18px; }
+ul.signatures li { font-family: ui-monospace, monospace; white-space: pre-wrap; }
+ul.imports li { font-family: ui-monospace, monospace; color: #59636e; }
+li.match { background: #fff8c5; }
+table { border-collapse: collapse; width: 100%; }
+th, td { text-align: left; padding: 4px 8px; border-bottom: 1px solid #d0d7de; vertical-align: top; }
+td.path { font-family: ui-monospace, monospace; }
+.empty { color: #59636e; }
+"#;
+
+/// Filters the tree as the user types: files match on their path or any
+/// signature, and directories without a match are hidden
+const SCRIPT: &str = r#"
+(function () {
+  var input = document.getElementById('search');
+  var count = document.getElementById('count');
+  var files = Array.prototype.slice.call(document.querySelectorAll('details.file'));
+  var dirs = Array.prototype.slice.call(document.querySelectorAll('details.dir')).reverse();
+  var texts = files.map(function (f) { return (f.title + '\n' + f.textContent).toLowerCase(); });
+
+  function update() {
+    var query = input.value.trim().toLowerCase();
+    var shown = 0;
+    files.forEach(function (file, i) {
+      var hit = !query || texts[i].indexOf(query) !== -1;
+      file.hidden = !hit;
+      if (hit) { shown++; }
+      file.querySelectorAll('li').forEach(function (li) {
+        li.classList.toggle('match', !!query && li.textContent.toLowerCase().indexOf(query) !== -1);
+      });
+      if (query && hit) { file.open = true; }
+    });
+    dirs.forEach(function (dir) {
+      var visible = !query || dir.querySelector('details.file:not([hidden])') !== null;
+      dir.hidden = !visible;
+      if (query && visible) { dir.open = true; }
+    });
+    count.textContent = query ? shown + ' of ' + files.length + ' files' : files.length + ' files';
+  }
+
+  input.addEventListener('input', update);
+  update();
+})();
+"#;
+
+/// A self-contained HTML report: collapsible directory tree with each file's
+/// signatures, client-side search, and errors, warnings and skipped files
+pub struct HtmlWriter;
+
+impl DocumentWriter for HtmlWriter {
+    fn render(&self, doc: &Document) -> TalosResult<Vec<u8>> {
+        let mut out = String::new();
+        let files = doc.files().count();
+        let signatures: usize = doc.files().map(|f| f.signatures.len()).sum();
+
+        out.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+        out.push_str(
+            "<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+             <title>Talos API index</title>\n",
+        );
+        out.push_str(&format!("<style>{}</style>\n</head>\n<body>\n", STYLE));
+
+        out.push_str("<header>\n<h1>Talos API index</h1>\n");
+        out.push_str(&format!(
+            "<p>{}, {}, {}, {} skipped &middot; scanned {}</p>\n",
+            plural(files, "file"),
+            plural(signatures, "signature"),
+            plural(doc.errors.len(), "error"),
+            doc.skipped.len(),
+            escape(&doc.last_updated)
+        ));
+        out.push_str(
+            "<input id=\"search\" type=\"search\" placeholder=\"Search paths and signatures\" \
+             autofocus><span id=\"count\"></span>\n</header>\n<main>\n",
+        );
+
+        if !doc.packages.is_empty() {
+            write_packages(&mut out, doc);
+        }
+
+        out.push_str("<h2>Files</h2>\n");
+        if files == 0 {
+            out.push_str("<p class=\"empty\">No files were summarized.</p>\n");
+        } else {
+            write_tree(&mut out, &build_tree(doc), true);
+        }
+
+        write_errors(&mut out, doc);
+        if !doc.warnings.is_empty() {
+            write_warnings(&mut out, doc);
+        }
+        if !doc.skipped.is_empty() {
+            write_skipped(&mut out, doc);
+        }
+
+        out.push_str(&format!(
+            "</main>\n<script>{}</script>\n</body>\n</html>\n",
+            SCRIPT
+        ));
+        Ok(out.into_bytes())
+    }
+}
+
+/// Directory tree node: subdirectories by name, then the files directly inside
+#[derive(Default)]
+struct TreeNode<'a> {
+    children: BTreeMap<String, TreeNode<'a>>,
+    files: Vec<&'a FileEntry>,
+}
+
+/// Nest every directory by path component, under its root label when there
+/// are several inputs
+fn build_tree(doc: &Document) -> TreeNode<'_> {
+    let mut tree = TreeNode::default();
+    for dir in doc.all_directories() {
+        let mut node = &mut tree;
+        let components = dir.root.iter().map(String::as_str).chain(
+            dir.directory_path
+                .split('/')
+                .filter(|c| *c != "." && !c.is_empty()),
+        );
+        for component in components {
+            node = node.children.entry(component.to_string()).or_default();
+        }
+        node.files.extend(dir.files.iter());
+    }
+    tree
+}
+
+fn write_tree(out: &mut String, node: &TreeNode, top: bool) {
+    for (name, child) in &node.children {
+        let files = count_files(child);
+        out.push_str(&format!(
+            "<details class=\"dir\"{}><summary>{}/<span class=\"meta\">{}</span></summary>\n",
+            if top { " open" } else { "" },
+            escape(name),
+            plural(files, "file")
+        ));
+        write_tree(out, child, false);
+        out.push_str("</details>\n");
+    }
+    let mut files = node.files.clone();
+    files.sort_by(|a, b| a.file_name.cmp(&b.file_name));
+    for file in files {
+        write_file(out, file);
+    }
+}
+
+fn count_files(node: &TreeNode) -> usize {
+    node.files.len() + node.children.values().map(count_files).sum::<usize>()
+}
+
+fn write_file(out: &mut String, file: &FileEntry) {
+    out.push_str(&format!(
+        "<details class=\"file\" title=\"{}\"><summary>{}<span class=\"meta\">{}</span>",
+        escape(&file.relative_file_path),
+        escape(&file.file_name),
+        plural(file.signatures.len(), "signature")
+    ));
+    if file.generated {
+        out.push_str("<span class=\"tag\">generated</span>");
+    }
+    if file.partial {
+        out.push_str("<span class=\"tag warn\">partial</span>");
+    }
+    out.push_str("</summary>\n");
+
+    if let Some(summary) = &file.summary {
+        out.push_str(&format!("<p>{}</p>\n", escape(summary)));
+    }
+    if !file.signatures.is_empty() {
+        out.push_str("<ul class=\"signatures\">\n");
+        for signature in &file.signatures {
+            out.push_str(&format!("<li>{}</li>\n", escape(signature)));
+        }
+        out.push_str("</ul>\n");
+    }
+    if !file.imports.is_empty() {
+        out.push_str("<ul class=\"imports\">\n");
+        for import in &file.imports {
+            let target = import
+                .resolved_path
+                .as_deref()
+                .map(|p| format!(" &rarr; {}", escape(p)))
+                .unwrap_or_default();
+            out.push_str(&format!(
+                "<li>import {}{}</li>\n",
+                escape(&import.specifier),
+                target
+            ));
+        }
+        out.push_str("</ul>\n");
+    }
+    out.push_str("</details>\n");
+}
+
+fn write_packages(out: &mut String, doc: &Document) {
+    out.push_str("<h2>Packages</h2>\n<table>\n<tr><th>Name</th><th>Kind</th><th>Version</th><th>Path</th><th>Entry points</th></tr>\n");
+    for package in &doc.packages {
+        out.push_str(&format!(
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td class=\"path\">{}</td><td class=\"path\">{}</td></tr>\n",
+            escape(&package.name),
+            package.kind.as_str(),
+            escape(package.version.as_deref().unwrap_or("")),
+            escape(&with_root(package.root.as_deref(), &package.package_path)),
+            escape(&package.entry_points.join(", "))
+        ));
+    }
+    out.push_str("</table>\n");
+}
+
+fn write_errors(out: &mut String, doc: &Document) {
+    out.push_str("<h2>Errors</h2>\n");
+    if doc.errors.is_empty() {
+        out.push_str("<p class=\"empty\">No errors.</p>\n");
+        return;
+    }
+    out.push_str("<table>\n<tr><th>Path</th><th>Kind</th><th>Error</th></tr>\n");
+    for error in &doc.errors {
+        let position = match (error.line, error.column) {
+            (Some(line), Some(column)) => format!(":{}:{}", line, column),
+            (Some(line), None) => format!(":{}", line),
+            _ => String::new(),
+        };
+        out.push_str(&format!(
+            "<tr><td class=\"path\">{}{}</td><td>{}</td><td>{}</td></tr>\n",
+            escape(&with_root(error.root.as_deref(), &error.path)),
+            position,
+            error.kind.as_str(),
+            escape(&error.error)
+        ));
+    }
+    out.push_str("</table>\n");
+}
+
+fn write_warnings(out: &mut String, doc: &Document) {
+    out.push_str("<h2>Warnings</h2>\n<table>\n<tr><th>Location</th><th>Message</th></tr>\n");
+    for warning in &doc.warnings {
+        out.push_str(&format!(
+            "<tr><td class=\"path\">{}:{}:{}</td><td>{}</td></tr>\n",
+            escape(&with_root(warning.root.as_deref(), &warning.path)),
+            warning.line,
+            warning.column,
+            escape(&warning.message)
+        ));
+    }
+    out.push_str("</table>\n");
+}
+
+fn write_skipped(out: &mut String, doc: &Document) {
+    out.push_str(
+        "<h2>Skipped</h2>\n<table>\n<tr><th>Path</th><th>Reason</th><th>Detail</th></tr>\n",
+    );
+    for skipped in &doc.skipped {
+        out.push_str(&format!(
+            "<tr><td class=\"path\">{}</td><td>{}</td><td>{}</td></tr>\n",
+            escape(&with_root(skipped.root.as_deref(), &skipped.path)),
+            skipped.reason.as_str(),
+            escape(&skipped.detail)
+        ));
+    }
+    out.push_str("</table>\n");
+}
+
+fn plural(n: usize, noun: &str) -> String {
+    if n == 1 {
+        format!("1 {}", noun)
+    } else {
+        format!("{} {}s", n, noun)
+    }
+}
+
+fn with_root(root: Option<&str>, path: &str) -> String {
+    match root {
+        Some(root) => format!("{}: {}", root, path),
+        None => path.to_string(),
+    }
+}
+
+/// Escape text for HTML content and double-quoted attributes
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::sample_document;
+
+    #[test]
+    fn report_is_escaped_and_self_contained() {
+        let mut doc = sample_document();
+        let file = &mut doc.directories[0].files[0];
+        file.relative_file_path = r#"src/<script>alert("x")</script>.ts"#.to_string();
+        file.file_name = r#"<script>alert("x")</script>.ts"#.to_string();
+        file.signatures = vec![r#"const tag = "</script><img onerror=x>""#.to_string()];
+
+        let out = String::from_utf8(HtmlWriter.render(&doc).unwrap()).unwrap();
+        // Only the report's own inline script
+        assert_eq!(out.matches("<script>").count(), 1);
+        assert_eq!(out.matches("</script>").count(), 1);
+        assert!(!out.contains("<img"));
+        assert!(out.contains(r#"title="src/&lt;script&gt;alert(&quot;x&quot;)&lt;/script&gt;.ts""#));
+        assert!(
+            out.contains("<li>const tag = &quot;&lt;/script&gt;&lt;img onerror=x&gt;&quot;</li>")
+        );
+
+        // Nothing loaded from elsewhere
+        for external in [" src=", " href=", "<link", "@import", "url("] {
+            assert!(!out.contains(external), "found {external:?}");
+        }
+    }
+}
@@ -1,6 +1,13 @@
 pub mod error;
 pub mod extractor;
+pub mod generated;
+pub mod graph;
+pub mod html;
 pub mod model;
+pub mod resolver;
 pub mod scanner;
+pub mod sqlite;
+pub mod symbols;
 pub mod types;
+pub mod workspace;
 pub mod writer;
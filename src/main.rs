@@ -1,12 +1,20 @@
-use clap::{ArgAction, Parser};
-use std::path::PathBuf;
+use clap::{ArgAction, Parser, Subcommand};
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::time::Duration;
 
 use talos::{
-    model::{Document, ErrorEntry},
-    scanner::{scan_project, ScanOptions},
-    types::{Extensions, GlobPatterns, MaxFileSize, OutputPath},
-    writer::write_output,
+    extractor::{ExtractorRegistry, QueryOverrides, SubprocessExtractor},
+    graph::render_graph,
+    model::{Document, ErrorEntry, SkipReason},
+    scanner::{explain_path, scan_inputs, stream_inputs, ScanOptions},
+    types::{
+        Extensions, GeneratedFiles, GlobPatterns, GraphFormat, LangMapping, MaxFileSize,
+        OutputFormat, OutputPath, PluginSpec,
+    },
+    writer::{document_writer, write_output, write_text, NdjsonWriter, WriterOptions},
 };
 
 /// Talos: Extract concise code signatures from projects (JS/TS first).
@@ -14,35 +22,138 @@ use talos::{
 #[command(
     name = "talos",
     version,
-    about = "Signature-focused code summarizer CLI"
+    about = "Signature-focused code summarizer CLI",
+    subcommand_negates_reqs = true
 )]
 struct Args {
-    /// Input directory path
-    input: PathBuf,
+    #[command(subcommand)]
+    command: Option<Command>,
 
-    /// Output filename (use '-' for stdout). Defaults to 'talos.json' in input dir.
+    /// Input directories or files; several are merged into one summary, each
+    /// entry labelled with the input it came from
+    #[arg(required = true)]
+    input: Vec<PathBuf>,
+
+    /// Output filename (use '-' for stdout). Defaults to 'talos.json' (or the --format
+    /// extension) in the input directory (the current directory when there are several inputs).
     #[arg(short, long)]
     output: Option<String>,
 
+    /// Output format: json, json-compact, yaml, toml, msgpack, ndjson, xml-prompt, html,
+    /// sqlite, ctags or lsif. Defaults from the --output extension (.yaml, .toml, .msgpack,
+    /// .ndjson, .xml, .html, .sqlite/.db, .lsif, or a file named 'tags'), otherwise json.
+    #[arg(long, value_name = "FORMAT")]
+    format: Option<String>,
+
+    /// Start xml-prompt output with a <project_layout> block listing packages and directories
+    #[arg(long, action = ArgAction::SetTrue)]
+    preamble: bool,
+
     /// Skip files with 0 signatures
     #[arg(long, action = ArgAction::SetTrue)]
     terse_output: bool,
 
-    /// Comma-separated list of allowed extensions (overrides defaults).
-    #[arg(long)]
+    /// List every file left out of the summary, with the reason, in a "skipped" section
+    #[arg(long, action = ArgAction::SetTrue)]
+    explain: bool,
+
+    /// Fail instead of writing output when any file has syntax or decoding errors
+    #[arg(long, action = ArgAction::SetTrue)]
+    strict: bool,
+
+    /// Write one JSON record per line while scanning, instead of building the whole
+    /// summary in memory (same as --format ndjson). Defaults to 'talos.ndjson'.
+    #[arg(long, action = ArgAction::SetTrue)]
+    stream: bool,
+
+    /// Comma-separated list of allowed extensions (defaults to every registered language).
+    #[arg(long, global = true)]
     ext: Option<String>,
 
-    /// Include glob (repeatable)
-    #[arg(long)]
+    /// Include glob, matched against the path relative to the input root, e.g. `src/**`; without
+    /// a `/` it matches the file name at any depth (repeatable)
+    #[arg(long, global = true)]
     include: Vec<String>,
 
-    /// Exclude glob (repeatable)
-    #[arg(long)]
+    /// Exclude glob, matched like --include, e.g. `*.test.*` (repeatable)
+    #[arg(long, global = true)]
     exclude: Vec<String>,
 
+    /// Don't apply the built-in excludes (**/node_modules/**, **/dist/**, **/.git/**, **/coverage/**)
+    #[arg(long, action = ArgAction::SetTrue, global = true)]
+    no_default_excludes: bool,
+
+    /// Descend into symlinked directories; symlink loops are reported as errors
+    #[arg(long, action = ArgAction::SetTrue, global = true)]
+    follow_symlinks: bool,
+
+    /// Skip dotfiles and dot-directories
+    #[arg(long, action = ArgAction::SetTrue, global = true)]
+    no_hidden: bool,
+
+    /// Ignore .gitignore, .git/info/exclude and the global gitignore (.ignore and .talosignore still apply)
+    #[arg(long, action = ArgAction::SetTrue, global = true)]
+    no_gitignore: bool,
+
+    /// Only walk this many levels below each input (1 = files directly inside it)
+    #[arg(long, value_name = "N", global = true)]
+    max_depth: Option<usize>,
+
     /// Max file size in bytes (skip larger files)
-    #[arg(long)]
+    #[arg(long, global = true)]
     max_file_size: Option<u64>,
+
+    /// Scan only the workspace package with this name (npm/yarn/pnpm workspace or Cargo member)
+    #[arg(long, value_name = "NAME")]
+    package: Option<String>,
+
+    /// Generated and minified files (`*.min.js`, `@generated` headers, `linguist-generated`):
+    /// skip (default), tag (summarize with "generated": true) or keep (no detection)
+    #[arg(long, value_name = "MODE", global = true)]
+    generated: Option<String>,
+
+    /// Directory of .scm files replacing the built-in signature queries
+    /// (javascript.scm, typescript.scm, tsx.scm, css.scm, java.scm, kotlin.scm, c.scm, cpp.scm,
+    /// csharp.scm, php.scm, ruby.scm)
+    #[arg(long, global = true)]
+    queries_dir: Option<PathBuf>,
+
+    /// External extractor as <ext>[,<ext>...]=<command> (repeatable). The command
+    /// speaks line-delimited JSON on stdin/stdout; see README.
+    #[arg(long = "plugin", value_name = "EXTS=COMMAND", global = true)]
+    plugins: Vec<String>,
+
+    /// Seconds a plugin may take to answer for one file before it is stopped and the
+    /// file reported under "errors" (default 30)
+    #[arg(long, value_name = "SECONDS", global = true)]
+    plugin_timeout: Option<u64>,
+
+    /// Parse files with extension <EXT> as another extension or language, e.g.
+    /// `es6=js` or `inc=php` (repeatable). Mapped extensions join the default --ext list.
+    #[arg(long = "lang-map", value_name = "EXT=LANG", global = true)]
+    lang_maps: Vec<String>,
+
+    /// Also write the import dependency graph to this file (use '-' for stdout)
+    #[arg(long)]
+    graph: Option<String>,
+
+    /// Graph format: dot or mermaid. Defaults from the --graph extension (.mmd = mermaid).
+    #[arg(long, requires = "graph")]
+    graph_format: Option<String>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Explain whether a file is summarized and, if not, which filter skips it.
+    /// Accepts the same filtering options as a scan.
+    Why {
+        /// File to explain
+        path: PathBuf,
+
+        /// Project root the scan would start from
+        #[arg(long, default_value = ".")]
+        root: PathBuf,
+    },
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -51,35 +162,223 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 }
 
 fn run(args: Args) -> Result<(), Box<dyn std::error::Error>> {
+    let query_overrides = match &args.queries_dir {
+        Some(dir) => QueryOverrides::load(dir).map_err(|e| format!("Invalid queries: {e}"))?,
+        None => QueryOverrides::default(),
+    };
+
+    let mut extractors = ExtractorRegistry::with_builtins(&query_overrides);
+    for spec in &args.plugins {
+        let spec =
+            PluginSpec::from_str(spec).map_err(|e| format!("Invalid --plugin '{spec}': {e}"))?;
+        let mut plugin = SubprocessExtractor::new(spec.name(), &spec.command)?;
+        if let Some(seconds) = args.plugin_timeout {
+            plugin = plugin.with_timeout(Duration::from_secs(seconds));
+        }
+        let exts: Vec<&str> = spec.extensions.iter().map(String::as_str).collect();
+        extractors.register(Box::new(plugin), &exts, &[]);
+    }
+
+    for mapping in &args.lang_maps {
+        let mapping = LangMapping::from_str(mapping)
+            .map_err(|e| format!("Invalid --lang-map '{mapping}': {e}"))?;
+        extractors
+            .map_extension(&mapping.extension, &mapping.target)
+            .map_err(|e| format!("Invalid --lang-map '{}': {e}", mapping.extension))?;
+    }
+
+    let default_extensions = || Extensions::new(extractors.extensions());
     let extensions = match &args.ext {
         Some(s) => Extensions::from_str(s).unwrap_or_else(|_| {
             eprintln!("Warning: Invalid extensions format, using defaults");
-            Extensions::default()
+            default_extensions()
         }),
-        None => Extensions::default(),
+        None => default_extensions(),
     };
 
-    let options = ScanOptions {
+    let generated = match args.generated.as_deref() {
+        Some(s) => {
+            GeneratedFiles::from_str(s).map_err(|e| format!("Invalid --generated '{s}': {e}"))?
+        }
+        None => GeneratedFiles::default(),
+    };
+
+    let mut options = ScanOptions {
         allowed_exts: extensions,
         include_globs: GlobPatterns::from(args.include),
         exclude_globs: GlobPatterns::from(args.exclude),
+        default_excludes: !args.no_default_excludes,
+        follow_links: args.follow_symlinks,
+        skip_hidden: args.no_hidden,
+        git_ignore: !args.no_gitignore,
+        max_depth: args.max_depth,
         max_file_size: args.max_file_size.map(MaxFileSize::new),
         terse_output: args.terse_output,
+        explain: args.explain,
+        generated,
+        package: args.package,
+        definitions: false,
+        extractors,
+    };
+
+    if let Some(Command::Why { path, root }) = &args.command {
+        return explain(root, path, &options);
+    }
+
+    if args.input.is_empty() {
+        return Err("Error: input directory is required".into());
+    }
+    if let Some(missing) = args.input.iter().find(|input| !input.exists()) {
+        return Err(format!("Error: input '{}' does not exist", missing.display()).into());
+    }
+    // Where default output files go
+    let root = match args.input.as_slice() {
+        [dir] if dir.is_dir() => dir.clone(),
+        [file] => file
+            .parent()
+            .filter(|p| !p.as_os_str().is_empty())
+            .map(Path::to_path_buf)
+            .unwrap_or_else(|| PathBuf::from(".")),
+        _ => PathBuf::from("."),
     };
 
-    let root = args.input;
-    if !root.is_dir() {
-        return Err("Error: input must be a directory".into());
+    let format = match (args.format.as_deref(), args.output.as_deref()) {
+        (Some(s), _) => {
+            OutputFormat::from_str(s).map_err(|e| format!("Invalid --format '{s}': {e}"))?
+        }
+        (None, _) if args.stream => OutputFormat::Ndjson,
+        (None, Some(path)) if path != "-" => OutputFormat::from_path(path),
+        (None, _) => OutputFormat::default(),
+    };
+    if args.stream && format != OutputFormat::Ndjson {
+        return Err("--stream always writes ndjson; drop --format or --stream".into());
     }
+    let output_path =
+        OutputPath::with_default_name(args.output.as_deref(), &root, format.default_file_name());
+    options.definitions = format.needs_definitions();
+
+    let writer_options = WriterOptions {
+        preamble: args.preamble,
+        project_root: fs::canonicalize(&root).unwrap_or_else(|_| root.clone()),
+    };
+    let Some(writer) = document_writer(format, &writer_options) else {
+        if args.graph.is_some() {
+            return Err("--graph needs the whole summary and cannot be used with ndjson".into());
+        }
+        return stream(&args.input, &options, &output_path, args.strict);
+    };
 
     let (mut doc, mut errors): (Document, Vec<ErrorEntry>) =
-        scan_project(&root, &options).map_err(|e| format!("Failed to scan project: {e}"))?;
+        scan_inputs(&args.input, &options).map_err(|e| format!("Failed to scan project: {e}"))?;
 
     if !errors.is_empty() {
         doc.errors.append(&mut errors);
     }
 
-    let output_path = OutputPath::new(args.output.as_deref(), &root);
+    if !doc.warnings.is_empty() {
+        let files: BTreeSet<&str> = doc.warnings.iter().map(|w| w.path.as_str()).collect();
+        if args.strict {
+            for w in &doc.warnings {
+                eprintln!("{}:{}:{}: {}", w.path, w.line, w.column, w.message);
+            }
+            return Err(format!(
+                "Syntax or decoding errors in {} file(s) (--strict)",
+                files.len()
+            )
+            .into());
+        }
+        eprintln!(
+            "Warning: syntax or decoding errors in {} file(s); their signatures may be incomplete (see \"warnings\")",
+            files.len()
+        );
+    }
+
+    let generated = doc
+        .skipped
+        .iter()
+        .filter(|s| s.reason == SkipReason::Generated)
+        .count();
+    note_generated(generated, "see \"skipped\"");
+
+    if let Some(graph) = args.graph.as_deref() {
+        let format = match args.graph_format.as_deref() {
+            Some(s) => GraphFormat::from_str(s)?,
+            None => GraphFormat::from_path(graph),
+        };
+        let graph_path = OutputPath::new(Some(graph), &root);
+        write_text(&render_graph(&doc, format), &graph_path)
+            .map_err(|e| format!("Failed to write graph: {e}"))?;
+    }
+
+    write_output(&doc, &output_path, writer.as_ref())
+        .map_err(|e| format!("Failed to write output: {e}").into())
+}
+
+/// `--stream`: write NDJSON records as the scan produces them
+fn stream(
+    inputs: &[PathBuf],
+    options: &ScanOptions,
+    output_path: &OutputPath,
+    strict: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut writer =
+        NdjsonWriter::create(output_path).map_err(|e| format!("Failed to write output: {e}"))?;
+    stream_inputs(inputs, options, &mut writer)
+        .map_err(|e| format!("Failed to scan project: {e}"))?;
+
+    let files = writer.warning_count();
+    if files > 0 {
+        // Records are already written; a file target is just never moved into place
+        if strict {
+            return Err(
+                format!("Syntax or decoding errors in {} file(s) (--strict)", files).into(),
+            );
+        }
+        eprintln!(
+            "Warning: syntax or decoding errors in {} file(s); their signatures may be incomplete (see \"warning\" records)",
+            files
+        );
+    }
+
+    note_generated(writer.generated_count(), "see \"skipped\" records");
+
+    writer
+        .finish()
+        .map_err(|e| format!("Failed to write output: {e}").into())
+}
 
-    write_output(&doc, &output_path).map_err(|e| format!("Failed to write output: {e}").into())
+fn note_generated(files: usize, see: &str) {
+    if files > 0 {
+        eprintln!(
+            "Note: skipped {} generated file(s) ({}; --generated tag keeps them)",
+            files, see
+        );
+    }
+}
+
+/// `talos why <path>`: print the verdict for one file
+fn explain(
+    root: &Path,
+    path: &Path,
+    options: &ScanOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let verdict = explain_path(root, path, options).map_err(|e| format!("Cannot explain: {e}"))?;
+    match verdict {
+        Some(skip) => println!(
+            "{}: skipped ({}): {}",
+            skip.path,
+            skip.reason.as_str(),
+            skip.detail
+        ),
+        None => {
+            let code = std::fs::read(path)?;
+            let extractor = options
+                .extractors
+                .detect(path, &String::from_utf8_lossy(&code))
+                .map(|e| e.name().to_string())
+                .unwrap_or_else(|| "no extractor".to_string());
+            println!("{}: included ({})", path.display(), extractor);
+        }
+    }
+    Ok(())
 }
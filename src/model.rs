@@ -1,32 +1,413 @@
+use crate::error::TalosError;
 use serde::{Deserialize, Serialize};
 
+/// Output schema version. 2.0 moved directories inside workspace packages from
+/// the top-level `directories` to `packages[].directories`.
+pub const SCHEMA_VERSION: &str = "2.0";
+
 #[derive(Debug, Serialize, Deserialize, Default)]
 pub struct Document {
     pub schema_version: String,
     pub last_updated: String,
+    /// Directories outside any workspace package
     pub directories: Vec<DirectoryEntry>,
+    /// Workspace packages (npm/yarn/pnpm workspaces, Cargo workspace members)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub packages: Vec<PackageEntry>,
+    /// Project dependency graph: one edge per resolved file-to-file import
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dependencies: Vec<DependencyEdge>,
     #[serde(default)]
     pub errors: Vec<ErrorEntry>,
+    /// Recoverable problems, such as syntax errors the parser worked around
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<WarningEntry>,
+    /// Files left out of the summary and why: generated files always, the rest
+    /// only with `--explain`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub skipped: Vec<SkippedEntry>,
+}
+
+impl Document {
+    /// Every directory, whether or not it belongs to a package
+    pub fn all_directories(&self) -> impl Iterator<Item = &DirectoryEntry> {
+        self.directories
+            .iter()
+            .chain(self.packages.iter().flat_map(|p| &p.directories))
+    }
+
+    /// Every summarized file
+    pub fn files(&self) -> impl Iterator<Item = &FileEntry> {
+        self.all_directories().flat_map(|d| &d.files)
+    }
+
+    /// Append another root's entries, keeping this document's metadata
+    pub fn merge(&mut self, other: Document) {
+        self.directories.extend(other.directories);
+        self.packages.extend(other.packages);
+        self.dependencies.extend(other.dependencies);
+        self.errors.extend(other.errors);
+        self.warnings.extend(other.warnings);
+        self.skipped.extend(other.skipped);
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PackageEntry {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub root: Option<String>,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    pub kind: PackageKind,
+    /// Package directory, relative to root
+    pub package_path: String,
+    /// Main, module, bin and library files, relative to root
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub entry_points: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub directories: Vec<DirectoryEntry>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PackageKind {
+    /// package.json member of an npm, yarn or pnpm workspace
+    Npm,
+    /// Cargo workspace member
+    Cargo,
+}
+
+impl PackageKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            PackageKind::Npm => "npm",
+            PackageKind::Cargo => "cargo",
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct DirectoryEntry {
+    /// Input the entry came from, when several were scanned together
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub root: Option<String>,
     pub directory_path: String, // relative to root
     pub files: Vec<FileEntry>,
 }
 
+/// Where a streamed file sits in the document hierarchy
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FileLocation {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub root: Option<String>,
+    /// `package_path` of the workspace package containing the file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub package_path: Option<String>,
+    pub directory_path: String,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct FileEntry {
     pub file_name: String,
     pub relative_file_path: String,
     pub last_scanned: String,
     pub signatures: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub imports: Vec<ImportEntry>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub components: Vec<ComponentEntry>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub summary: Option<String>,
+    /// True when the file had syntax or decoding errors, so `signatures` may be incomplete
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub partial: bool,
+    /// Encoding the source was decoded from, when not plain UTF-8 (e.g. `windows-1252`, `UTF-16LE`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encoding: Option<String>,
+    /// Generated or minified, when kept with `--generated tag`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub generated: bool,
+    /// Named declarations with positions; only kept for symbol index formats (ctags, LSIF)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub definitions: Vec<DefinitionEntry>,
+}
+
+/// A named declaration, located by its name
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DefinitionEntry {
+    pub name: String,
+    /// function, method, class, interface, struct, macro, ...
+    pub kind: String,
+    /// 1-based position of the name; columns count UTF-16 code units, as in LSP
+    pub line: usize,
+    pub column: usize,
+    /// Innermost enclosing definition as `kind:name`, e.g. `class:Widget`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ImportEntry {
+    pub specifier: String,
+    /// Scanned file the specifier points at, relative to root
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resolved_path: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ComponentEntry {
+    pub name: String,
+    /// function, arrow, forwardRef, memo or class
+    pub kind: String,
+    /// Props type or interface name, when annotated
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub props: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hooks: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DependencyEdge {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub root: Option<String>,
+    pub from: String,
+    pub to: String,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ErrorEntry {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub root: Option<String>,
     pub path: String,
+    #[serde(default)]
+    pub kind: ErrorKind,
     pub error: String,
+    /// 1-based position within `path`, when the cause has one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub column: Option<usize>,
+}
+
+impl ErrorEntry {
+    pub fn new(path: impl Into<String>, kind: ErrorKind, error: impl Into<String>) -> Self {
+        ErrorEntry {
+            root: None,
+            path: path.into(),
+            kind,
+            error: error.into(),
+            line: None,
+            column: None,
+        }
+    }
+
+    /// Entry for `error` at `path`, classified and positioned from the error itself
+    pub fn from_error(path: impl Into<String>, error: &TalosError) -> Self {
+        let (line, column) = error.position().unzip();
+        ErrorEntry {
+            line,
+            column,
+            ..ErrorEntry::new(path, error.kind(), error.to_string())
+        }
+    }
+}
+
+/// Cause of an [`ErrorEntry`], for grouping failures
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ErrorKind {
+    /// Reading or writing failed
+    #[default]
+    Io,
+    /// Access denied
+    Permission,
+    /// Content could not be decoded as text
+    Encoding,
+    /// Source or config file could not be parsed
+    Parse,
+    /// Invalid tree-sitter query
+    Query,
+    /// File exceeded `--max-file-size`
+    SizeLimit,
+    /// Directory traversal failed (unreadable directory, bad ignore file, symlink loop)
+    Walk,
+}
+
+impl ErrorKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ErrorKind::Io => "io",
+            ErrorKind::Permission => "permission",
+            ErrorKind::Encoding => "encoding",
+            ErrorKind::Parse => "parse",
+            ErrorKind::Query => "query",
+            ErrorKind::SizeLimit => "size-limit",
+            ErrorKind::Walk => "walk",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WarningEntry {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub root: Option<String>,
+    pub path: String,
+    /// 1-based position in the file
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkippedEntry {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub root: Option<String>,
+    pub path: String,
+    pub reason: SkipReason,
+    /// The rule responsible, e.g. the matching glob or the file size
+    pub detail: String,
+}
+
+/// Filter that left a file out of the summary
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SkipReason {
+    /// Matched by `.gitignore`, `.ignore`, `.talosignore` or `.git/info/exclude`
+    Ignored,
+    /// Matched an `--exclude` glob or a default exclude
+    Excluded,
+    /// `--include` globs were given and none matched
+    NotIncluded,
+    /// Extension not in `--ext` and no extractor recognised the file
+    Extension,
+    /// Larger than `--max-file-size`
+    SizeLimit,
+    /// Generated or minified (see `--generated`)
+    Generated,
+    /// Dotfile or inside a dot-directory, with `--no-hidden`
+    Hidden,
+    /// Below `--max-depth`
+    Depth,
+    /// Reached through a symlinked directory without `--follow-symlinks`
+    Symlink,
+}
+
+impl SkipReason {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SkipReason::Ignored => "ignored",
+            SkipReason::Excluded => "excluded",
+            SkipReason::NotIncluded => "not-included",
+            SkipReason::Extension => "extension",
+            SkipReason::SizeLimit => "size-limit",
+            SkipReason::Generated => "generated",
+            SkipReason::Hidden => "hidden",
+            SkipReason::Depth => "depth",
+            SkipReason::Symlink => "symlink",
+        }
+    }
+}
+
+/// A small document touching every section, for the writer tests
+#[cfg(test)]
+pub(crate) fn sample_document() -> Document {
+    let file = |path: &str, signatures: &[&str], definitions: Vec<DefinitionEntry>| FileEntry {
+        file_name: path.rsplit('/').next().unwrap_or(path).to_string(),
+        relative_file_path: path.to_string(),
+        last_scanned: "2024-01-01T00:00:00Z".to_string(),
+        signatures: signatures.iter().map(|s| s.to_string()).collect(),
+        imports: Vec::new(),
+        components: Vec::new(),
+        summary: None,
+        partial: false,
+        encoding: None,
+        generated: false,
+        definitions,
+    };
+    let definition = |name: &str, kind: &str, line, column, scope: Option<&str>| DefinitionEntry {
+        name: name.to_string(),
+        kind: kind.to_string(),
+        line,
+        column,
+        scope: scope.map(String::from),
+    };
+
+    let mut widget = file(
+        "src/widget.tsx",
+        &["export class Widget", "method render(props: Props)"],
+        vec![
+            definition("Widget", "class", 3, 14, None),
+            definition("render", "method", 4, 3, Some("class:Widget")),
+        ],
+    );
+    widget.imports = vec![
+        ImportEntry {
+            specifier: "./util".to_string(),
+            resolved_path: Some("src/util.ts".to_string()),
+        },
+        ImportEntry {
+            specifier: "react".to_string(),
+            resolved_path: None,
+        },
+    ];
+    widget.components = vec![ComponentEntry {
+        name: "Widget".to_string(),
+        kind: "class".to_string(),
+        props: Some("Props".to_string()),
+        hooks: Vec::new(),
+    }];
+    let util = file(
+        "src/util.ts",
+        &["export function alpha(a: number)"],
+        vec![definition("alpha", "function", 1, 17, None)],
+    );
+    let mut core = file("packages/core/src/lib.rs", &["pub fn run()"], Vec::new());
+    core.partial = true;
+
+    Document {
+        schema_version: SCHEMA_VERSION.to_string(),
+        last_updated: "2024-01-01T00:00:00Z".to_string(),
+        directories: vec![DirectoryEntry {
+            root: None,
+            directory_path: "src".to_string(),
+            files: vec![util, widget],
+        }],
+        packages: vec![PackageEntry {
+            root: None,
+            name: "core".to_string(),
+            version: None,
+            kind: PackageKind::Cargo,
+            package_path: "packages/core".to_string(),
+            entry_points: vec!["packages/core/src/lib.rs".to_string()],
+            directories: vec![DirectoryEntry {
+                root: None,
+                directory_path: "packages/core/src".to_string(),
+                files: vec![core],
+            }],
+        }],
+        dependencies: vec![DependencyEdge {
+            root: None,
+            from: "src/widget.tsx".to_string(),
+            to: "src/util.ts".to_string(),
+        }],
+        errors: vec![ErrorEntry::new(
+            "src/broken.js",
+            ErrorKind::Encoding,
+            "bad bytes",
+        )],
+        warnings: vec![WarningEntry {
+            root: None,
+            path: "packages/core/src/lib.rs".to_string(),
+            line: 2,
+            column: 5,
+            message: "syntax error".to_string(),
+        }],
+        skipped: vec![SkippedEntry {
+            root: None,
+            path: "dist".to_string(),
+            reason: SkipReason::Excluded,
+            detail: "directory matches exclude glob `**/dist/**`".to_string(),
+        }],
+    }
 }
@@ -0,0 +1,662 @@
+use crate::error::TalosResult;
+use crate::extractor::SupportedLang;
+use crate::model::ErrorEntry;
+use crate::workspace::ProjectFiles;
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+/// Extensions tried, in order, when a specifier omits one
+const RESOLVE_EXTENSIONS: &[&str] = &["ts", "tsx", "mts", "cts", "js", "jsx", "mjs", "cjs", "css"];
+
+/// `exports` conditions we follow, most source-like first
+const EXPORT_CONDITIONS: &[&str] = &[
+    "source", "types", "import", "module", "require", "node", "default",
+];
+
+/// Build output directories that usually mirror `src/` in workspace packages
+const BUILD_DIRS: &[&str] = &["dist", "lib", "build", "out", "esm", "cjs"];
+
+/// Maximum depth of `extends` chains followed in tsconfig files
+const MAX_EXTENDS_DEPTH: usize = 8;
+
+/// Maps import specifiers to scanned files, relative to the project root.
+///
+/// Handles relative specifiers, `tsconfig.json`/`jsconfig.json` `baseUrl` and
+/// `paths`, and bare specifiers naming a workspace package (via its
+/// `package.json` `exports`, falling back to `main`/`module`).
+pub struct ImportResolver {
+    known_files: BTreeSet<String>,
+    tsconfigs: Vec<TsConfig>,
+    packages: Vec<WorkspacePackage>,
+}
+
+struct TsConfig {
+    /// Directory containing the config, relative to root
+    dir: PathBuf,
+    base_url: Option<PathBuf>,
+    /// Directory `paths` targets are relative to (baseUrl, or the defining config's dir)
+    paths_base: PathBuf,
+    /// `(pattern, targets)`, longest pattern prefix first
+    paths: Vec<(String, Vec<String>)>,
+}
+
+struct WorkspacePackage {
+    name: String,
+    dir: PathBuf,
+    exports: Option<Value>,
+    entry_points: Vec<String>,
+}
+
+impl ImportResolver {
+    /// Resolver that only understands relative specifiers
+    pub fn new(known_files: BTreeSet<String>) -> Self {
+        ImportResolver {
+            known_files,
+            tsconfigs: Vec::new(),
+            packages: Vec::new(),
+        }
+    }
+
+    /// Load the tsconfig/jsconfig and package.json files found under `root`.
+    /// Config files that cannot be read or parsed are reported as errors and skipped.
+    pub fn discover(
+        root: &Path,
+        files: &ProjectFiles,
+        known_files: BTreeSet<String>,
+    ) -> (Self, Vec<ErrorEntry>) {
+        let mut resolver = ImportResolver::new(known_files);
+        let mut errors = Vec::new();
+
+        for path in files.js_configs(root) {
+            let rel_dir = path
+                .parent()
+                .and_then(|p| p.strip_prefix(root).ok())
+                .map(Path::to_path_buf)
+                .unwrap_or_default();
+
+            let loaded = if path.ends_with("package.json") {
+                load_package(&path, &rel_dir).map(|pkg| {
+                    if let Some(pkg) = pkg {
+                        resolver.packages.push(pkg);
+                    }
+                })
+            } else {
+                load_tsconfig(root, &path, &rel_dir).map(|cfg| resolver.tsconfigs.push(cfg))
+            };
+
+            if let Err(error) = loaded {
+                let rel_path = path.strip_prefix(root).unwrap_or(&path);
+                errors.push(ErrorEntry::from_error(rel_path.to_string_lossy(), &error));
+            }
+        }
+
+        // Nearest config wins, longest package name wins
+        resolver
+            .tsconfigs
+            .sort_by_key(|cfg| std::cmp::Reverse(cfg.dir.components().count()));
+        resolver
+            .packages
+            .sort_by_key(|pkg| std::cmp::Reverse(pkg.name.len()));
+
+        (resolver, errors)
+    }
+
+    /// Resolve `specifier`, as written in `from_file`, to a scanned file
+    pub fn resolve(&self, from_file: &str, specifier: &str) -> Option<String> {
+        let specifier = strip_query(specifier);
+
+        if is_relative_specifier(specifier)
+            || is_css_relative(from_file, specifier)
+            || is_include_relative(from_file, specifier)
+        {
+            let base = Path::new(from_file)
+                .parent()
+                .unwrap_or_else(|| Path::new(""));
+            return self.first_known(&base.join(specifier));
+        }
+
+        self.resolve_tsconfig(from_file, specifier)
+            .or_else(|| self.resolve_package(specifier))
+    }
+
+    fn resolve_tsconfig(&self, from_file: &str, specifier: &str) -> Option<String> {
+        let cfg = self
+            .tsconfigs
+            .iter()
+            .find(|cfg| Path::new(from_file).starts_with(&cfg.dir))?;
+
+        for (pattern, targets) in &cfg.paths {
+            let Some(wildcard) = match_pattern(pattern, specifier) else {
+                continue;
+            };
+            let found = targets.iter().find_map(|target| {
+                self.first_known(&cfg.paths_base.join(target.replacen('*', wildcard, 1)))
+            });
+            if found.is_some() {
+                return found;
+            }
+        }
+
+        cfg.base_url
+            .as_ref()
+            .and_then(|base| self.first_known(&base.join(specifier)))
+    }
+
+    fn resolve_package(&self, specifier: &str) -> Option<String> {
+        let pkg = self.packages.iter().find(|pkg| {
+            specifier == pkg.name || specifier.starts_with(&format!("{}/", pkg.name))
+        })?;
+        let subpath = match &specifier[pkg.name.len()..] {
+            "" => ".".to_string(),
+            rest => format!(".{}", rest),
+        };
+
+        let mut targets = match &pkg.exports {
+            Some(exports) => export_targets(exports, &subpath),
+            None => Vec::new(),
+        };
+        if subpath == "." {
+            targets.extend(pkg.entry_points.iter().cloned());
+        }
+        // Fall back to the package layout itself
+        targets.push(subpath.clone());
+        targets.push(format!("./src/{}", subpath.trim_start_matches("./")));
+
+        targets
+            .iter()
+            .find_map(|target| self.first_known(&pkg.dir.join(target)))
+    }
+
+    /// First scanned file matching `target` or one of its source equivalents
+    fn first_known(&self, target: &Path) -> Option<String> {
+        let target = normalize(target)?;
+        std::iter::once(target.clone())
+            .chain(source_equivalent(&target))
+            .flat_map(|t| resolution_candidates(&t))
+            .find(|candidate| self.known_files.contains(candidate))
+    }
+}
+
+fn is_relative_specifier(specifier: &str) -> bool {
+    specifier == "."
+        || specifier == ".."
+        || specifier.starts_with("./")
+        || specifier.starts_with("../")
+}
+
+/// CSS `@import "base.css"` is relative to the stylesheet even without `./`
+fn is_css_relative(from_file: &str, specifier: &str) -> bool {
+    from_file.to_ascii_lowercase().ends_with(".css")
+        && !specifier.contains("://")
+        && !specifier.starts_with(['/', '~'])
+}
+
+/// C/C++ `#include "widget.h"` is relative to the including file; `<...>` is not
+fn is_include_relative(from_file: &str, specifier: &str) -> bool {
+    let ext = Path::new(from_file)
+        .extension()
+        .map(|e| e.to_string_lossy().to_ascii_lowercase())
+        .unwrap_or_default();
+    let is_c_family = [SupportedLang::C, SupportedLang::Cpp]
+        .iter()
+        .any(|lang| lang.extensions().contains(&ext.as_str()));
+    is_c_family && !specifier.starts_with('<')
+}
+
+/// Drop bundler suffixes such as `?inline` or `#hash`
+fn strip_query(specifier: &str) -> &str {
+    specifier
+        .find(['?', '#'])
+        .map(|idx| &specifier[..idx])
+        .unwrap_or(specifier)
+}
+
+/// Lexically normalize `.` and `..` components; `None` if the path escapes the root
+fn normalize(path: &Path) -> Option<PathBuf> {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !out.pop() {
+                    return None;
+                }
+            }
+            other => out.push(other.as_os_str()),
+        }
+    }
+    Some(out)
+}
+
+fn resolution_candidates(target: &Path) -> Vec<String> {
+    let mut candidates = vec![target.to_string_lossy().to_string()];
+    let raw = target.to_string_lossy();
+
+    // Declaration files stand in for their sources
+    if let Some(stem) = raw.strip_suffix(".d.ts") {
+        return RESOLVE_EXTENSIONS
+            .iter()
+            .map(|ext| format!("{}.{}", stem, ext))
+            .collect();
+    }
+
+    // TypeScript projects import `./foo.js` while the source is `foo.ts`
+    if let Some(ext) = target.extension().and_then(|e| e.to_str()) {
+        let ts_exts: &[&str] = match ext {
+            "js" | "jsx" => &["ts", "tsx"],
+            "mjs" => &["mts", "ts", "tsx"],
+            "cjs" => &["cts", "ts", "tsx"],
+            _ => &[],
+        };
+        for ts_ext in ts_exts {
+            candidates.push(target.with_extension(ts_ext).to_string_lossy().to_string());
+        }
+    }
+
+    for ext in RESOLVE_EXTENSIONS {
+        candidates.push(format!("{}.{}", raw, ext));
+    }
+    for ext in RESOLVE_EXTENSIONS {
+        candidates.push(
+            target
+                .join(format!("index.{}", ext))
+                .to_string_lossy()
+                .to_string(),
+        );
+    }
+
+    candidates
+}
+
+/// `pkg/dist/format.js` -> `pkg/src/format.js` when the build output isn't scanned
+fn source_equivalent(target: &Path) -> Option<PathBuf> {
+    let components: Vec<Component> = target.components().collect();
+    let idx = components
+        .iter()
+        .position(|c| BUILD_DIRS.contains(&c.as_os_str().to_string_lossy().as_ref()))?;
+
+    let mut out = PathBuf::new();
+    for (i, component) in components.iter().enumerate() {
+        if i == idx {
+            out.push("src");
+        } else {
+            out.push(component.as_os_str());
+        }
+    }
+    Some(out)
+}
+
+/// Match a tsconfig `paths` / `exports` pattern with at most one `*`,
+/// returning the text the wildcard stands for
+fn match_pattern<'a>(pattern: &str, specifier: &'a str) -> Option<&'a str> {
+    match pattern.split_once('*') {
+        None => (pattern == specifier).then_some(""),
+        Some((prefix, suffix)) => {
+            if specifier.len() >= prefix.len() + suffix.len()
+                && specifier.starts_with(prefix)
+                && specifier.ends_with(suffix)
+            {
+                Some(&specifier[prefix.len()..specifier.len() - suffix.len()])
+            } else {
+                None
+            }
+        }
+    }
+}
+
+/// Targets of a package.json `exports` field for `subpath` (`.` or `./x`)
+fn export_targets(exports: &Value, subpath: &str) -> Vec<String> {
+    let subpath_map = match exports {
+        Value::Object(map) if map.keys().all(|k| k.starts_with('.')) => map,
+        // Sugar: a string, array or conditions object exports "." only
+        other => {
+            return if subpath == "." {
+                condition_targets(other, "")
+            } else {
+                Vec::new()
+            };
+        }
+    };
+
+    if let Some(value) = subpath_map.get(subpath) {
+        return condition_targets(value, "");
+    }
+
+    // Longest matching pattern key wins
+    subpath_map
+        .iter()
+        .filter_map(|(key, value)| match_pattern(key, subpath).map(|w| (key, w, value)))
+        .max_by_key(|(key, _, _)| key.find('*').unwrap_or(key.len()))
+        .map(|(_, wildcard, value)| condition_targets(value, wildcard))
+        .unwrap_or_default()
+}
+
+fn condition_targets(value: &Value, wildcard: &str) -> Vec<String> {
+    match value {
+        Value::String(target) => vec![target.replace('*', wildcard)],
+        Value::Array(items) => items
+            .iter()
+            .flat_map(|item| condition_targets(item, wildcard))
+            .collect(),
+        Value::Object(conditions) => EXPORT_CONDITIONS
+            .iter()
+            .filter_map(|cond| conditions.get(*cond))
+            .flat_map(|v| condition_targets(v, wildcard))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn load_tsconfig(root: &Path, path: &Path, rel_dir: &Path) -> TalosResult<TsConfig> {
+    let mut base_url: Option<PathBuf> = None;
+    let mut paths: Option<(PathBuf, serde_json::Map<String, Value>)> = None;
+
+    // Walk the `extends` chains; the first config to set an option wins. A config
+    // overrides what it extends, and later entries of an `extends` array override
+    // earlier ones, so visit it, then its bases last to first.
+    let mut pending = vec![(path.to_path_buf(), 0)];
+    while let Some((config_path, depth)) = pending.pop() {
+        let json = read_jsonc(&config_path)?;
+        let config_dir = config_path.parent().unwrap_or(root).to_path_buf();
+        let options = json.get("compilerOptions");
+
+        if base_url.is_none() {
+            if let Some(url) = options
+                .and_then(|o| o.get("baseUrl"))
+                .and_then(Value::as_str)
+            {
+                base_url = Some(config_dir.join(url));
+            }
+        }
+        if paths.is_none() {
+            if let Some(map) = options
+                .and_then(|o| o.get("paths"))
+                .and_then(Value::as_object)
+            {
+                paths = Some((config_dir.clone(), map.clone()));
+            }
+        }
+
+        if depth + 1 < MAX_EXTENDS_DEPTH {
+            let extends = match json.get("extends") {
+                Some(Value::String(ext)) => vec![ext.as_str()],
+                Some(Value::Array(exts)) => exts.iter().filter_map(Value::as_str).collect(),
+                _ => Vec::new(),
+            };
+            pending.extend(
+                extends
+                    .into_iter()
+                    .filter_map(|ext| extended_config(&config_dir, ext))
+                    .map(|ext| (ext, depth + 1)),
+            );
+        }
+    }
+
+    let to_rel = |abs: &Path| normalize(abs.strip_prefix(root).ok()?);
+    let base_url = base_url.as_deref().and_then(to_rel);
+
+    let (paths_base, paths) = match paths {
+        Some((defining_dir, map)) => {
+            let mut entries: Vec<(String, Vec<String>)> = map
+                .into_iter()
+                .map(|(pattern, targets)| {
+                    let targets = targets
+                        .as_array()
+                        .map(|arr| {
+                            arr.iter()
+                                .filter_map(Value::as_str)
+                                .map(String::from)
+                                .collect()
+                        })
+                        .unwrap_or_default();
+                    (pattern, targets)
+                })
+                .collect();
+            entries.sort_by_key(|(pattern, _)| {
+                std::cmp::Reverse(pattern.find('*').unwrap_or(pattern.len()))
+            });
+            let base = base_url
+                .clone()
+                .or_else(|| to_rel(&defining_dir))
+                .unwrap_or_default();
+            (base, entries)
+        }
+        None => (PathBuf::new(), Vec::new()),
+    };
+
+    Ok(TsConfig {
+        dir: rel_dir.to_path_buf(),
+        base_url,
+        paths_base,
+        paths,
+    })
+}
+
+/// File named by an `extends` entry: a path relative to the config, or a
+/// package under a `node_modules` directory (e.g. `@tsconfig/node18`), whose
+/// `tsconfig.json` is used unless the entry names a file. Packages that aren't
+/// installed are ignored.
+fn extended_config(config_dir: &Path, ext: &str) -> Option<PathBuf> {
+    // `./base` means `./base.json`, as does `./tsconfig.base`
+    let with_json = |path: PathBuf| {
+        if path.is_file() || path.extension().is_some_and(|e| e == "json") {
+            path
+        } else {
+            let mut name = path.into_os_string();
+            name.push(".json");
+            PathBuf::from(name)
+        }
+    };
+
+    if ext.starts_with('.') || Path::new(ext).is_absolute() {
+        return Some(with_json(config_dir.join(ext)));
+    }
+
+    config_dir.ancestors().find_map(|dir| {
+        let target = dir.join("node_modules").join(ext);
+        if target.is_dir() {
+            let config = target.join("tsconfig.json");
+            config.is_file().then_some(config)
+        } else {
+            let file = with_json(target);
+            file.is_file().then_some(file)
+        }
+    })
+}
+
+fn load_package(path: &Path, rel_dir: &Path) -> TalosResult<Option<WorkspacePackage>> {
+    let json = read_jsonc(path)?;
+    let Some(name) = json.get("name").and_then(Value::as_str) else {
+        return Ok(None);
+    };
+
+    let entry_points = ["source", "module", "main", "types", "typings"]
+        .iter()
+        .filter_map(|field| json.get(*field).and_then(Value::as_str))
+        .map(String::from)
+        .collect();
+
+    Ok(Some(WorkspacePackage {
+        name: name.to_string(),
+        dir: rel_dir.to_path_buf(),
+        exports: json.get("exports").cloned(),
+        entry_points,
+    }))
+}
+
+/// Read JSON that may contain comments and trailing commas (tsconfig style)
+fn read_jsonc(path: &Path) -> TalosResult<Value> {
+    let raw = fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&strip_jsonc(&raw))?)
+}
+
+fn strip_jsonc(input: &str) -> String {
+    let mut out = String::with_capacity(input.len());
+    let mut chars = input.chars().peekable();
+    let mut in_string = false;
+
+    while let Some(c) = chars.next() {
+        if in_string {
+            out.push(c);
+            match c {
+                '\\' => {
+                    if let Some(escaped) = chars.next() {
+                        out.push(escaped);
+                    }
+                }
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+
+        match (c, chars.peek()) {
+            ('"', _) => {
+                in_string = true;
+                out.push(c);
+            }
+            ('/', Some('/')) => {
+                for next in chars.by_ref() {
+                    if next == '\n' {
+                        out.push('\n');
+                        break;
+                    }
+                }
+            }
+            ('/', Some('*')) => {
+                chars.next();
+                let mut prev = '\0';
+                for next in chars.by_ref() {
+                    if prev == '*' && next == '/' {
+                        break;
+                    }
+                    prev = next;
+                }
+            }
+            (',', _) => {
+                // Drop trailing commas before a closing bracket
+                let rest = chars.clone().find(|ch| !ch.is_whitespace());
+                if !matches!(rest, Some('}') | Some(']')) {
+                    out.push(c);
+                }
+            }
+            _ => out.push(c),
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(root: &Path, rel: &str, contents: &str) {
+        let path = root.join(rel);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+
+    fn resolver(root: &Path, known: &[&str]) -> ImportResolver {
+        let known = known.iter().map(|f| f.to_string()).collect();
+        let (resolver, errors) = ImportResolver::discover(root, &ProjectFiles::find(root), known);
+        assert!(errors.is_empty(), "{:?}", errors);
+        resolver
+    }
+
+    #[test]
+    fn strip_jsonc_drops_comments_and_trailing_commas() {
+        let input = r#"{
+  // line comment
+  "a": "http://x.test/*not a comment*/", /* block */
+  "b": [1, 2,],
+}"#;
+        let value: Value = serde_json::from_str(&strip_jsonc(input)).unwrap();
+        assert_eq!(value["a"], "http://x.test/*not a comment*/");
+        assert_eq!(value["b"], serde_json::json!([1, 2]));
+    }
+
+    #[test]
+    fn match_pattern_returns_the_wildcard() {
+        assert_eq!(match_pattern("@app/*", "@app/ui/button"), Some("ui/button"));
+        assert_eq!(match_pattern("*.css", "theme.css"), Some("theme"));
+        assert_eq!(match_pattern("exact", "exact"), Some(""));
+        assert_eq!(match_pattern("exact", "exactly"), None);
+        assert_eq!(match_pattern("a*a", "a"), None);
+    }
+
+    #[test]
+    fn export_targets_follow_subpaths_patterns_and_conditions() {
+        let exports = serde_json::json!({
+            ".": { "types": "./dist/index.d.ts", "import": "./dist/index.mjs" },
+            "./utils/*": "./src/utils/*.ts",
+            "./utils/internal/*": null,
+            "./package.json": "./package.json"
+        });
+        assert_eq!(
+            export_targets(&exports, "."),
+            vec!["./dist/index.d.ts", "./dist/index.mjs"]
+        );
+        assert_eq!(
+            export_targets(&exports, "./utils/date"),
+            vec!["./src/utils/date.ts"]
+        );
+        assert!(export_targets(&exports, "./utils/internal/x").is_empty());
+        assert!(export_targets(&exports, "./missing").is_empty());
+
+        let sugar = serde_json::json!("./index.js");
+        assert_eq!(export_targets(&sugar, "."), vec!["./index.js"]);
+        assert!(export_targets(&sugar, "./sub").is_empty());
+    }
+
+    #[test]
+    fn tsconfig_extends_arrays_let_later_entries_win() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        write(
+            root,
+            "tsconfig.json",
+            r#"{ "extends": ["./first", "./second.json"] }"#,
+        );
+        write(
+            root,
+            "first.json",
+            r#"{ "compilerOptions": { "paths": { "@/*": ["old/*"] } } }"#,
+        );
+        write(
+            root,
+            "second.json",
+            r#"{ "compilerOptions": { "paths": { "@/*": ["src/*"] } } }"#,
+        );
+
+        let resolver = resolver(root, &["old/a.ts", "src/a.ts", "main.ts"]);
+        assert_eq!(
+            resolver.resolve("main.ts", "@/a"),
+            Some("src/a.ts".to_string())
+        );
+    }
+
+    #[test]
+    fn tsconfig_extends_installed_packages() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        write(
+            root,
+            "tsconfig.json",
+            r#"{ "extends": "@company/tsconfig" }"#,
+        );
+        write(
+            root,
+            "node_modules/@company/tsconfig/tsconfig.json",
+            r#"{ "compilerOptions": { "baseUrl": "../../../src" } }"#,
+        );
+
+        let resolver = resolver(root, &["src/util/date.ts", "main.ts"]);
+        assert_eq!(
+            resolver.resolve("main.ts", "util/date"),
+            Some("src/util/date.ts".to_string())
+        );
+    }
+}
@@ -1,110 +1,880 @@
 use crate::error::{TalosError, TalosResult};
-use crate::extractor::extract_signatures_for_file;
-use crate::model::{DirectoryEntry, Document, ErrorEntry, FileEntry};
-use crate::types::{Extensions, GlobPatterns, MaxFileSize};
-use globset::{Glob, GlobSet, GlobSetBuilder};
-use ignore::WalkBuilder;
-use std::collections::BTreeMap;
+use crate::extractor::{declared_symbol, read_source, CSymbol, ExtractorRegistry};
+use crate::generated::GeneratedDetector;
+use crate::graph::link_file;
+use crate::model::{
+    DependencyEdge, DirectoryEntry, Document, ErrorEntry, ErrorKind, FileEntry, FileLocation,
+    ImportEntry, PackageEntry, SkipReason, SkippedEntry, WarningEntry, SCHEMA_VERSION,
+};
+use crate::resolver::ImportResolver;
+use crate::types::{Extensions, GeneratedFiles, GlobPatterns, MaxFileSize};
+use crate::workspace::{discover_packages, Package, ProjectFiles};
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use ignore::gitignore::GitignoreBuilder;
+use ignore::{Match, WalkBuilder};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 use time::OffsetDateTime;
 
 pub struct ScanOptions {
     pub allowed_exts: Extensions,
     pub include_globs: GlobPatterns,
     pub exclude_globs: GlobPatterns,
+    /// Apply [`DEFAULT_EXCLUDES`] before `exclude_globs`
+    pub default_excludes: bool,
+    /// Descend into symlinked directories (loops are reported, not followed)
+    pub follow_links: bool,
+    /// Skip dotfiles and dot-directories
+    pub skip_hidden: bool,
+    /// Honor `.gitignore`, `.git/info/exclude` and the global gitignore
+    pub git_ignore: bool,
+    /// Deepest level walked below the input root; files directly inside it are depth 1
+    pub max_depth: Option<usize>,
     pub max_file_size: Option<MaxFileSize>,
     pub terse_output: bool,
+    /// Record every file left out, and why, in `Document::skipped`
+    pub explain: bool,
+    /// Skip, tag or keep generated and minified files
+    pub generated: GeneratedFiles,
+    /// Scan only the workspace package with this name
+    pub package: Option<String>,
+    /// Keep each file's definitions with positions (for the ctags and LSIF exports)
+    pub definitions: bool,
+    /// Language extractors by extension / file name
+    pub extractors: ExtractorRegistry,
 }
 
+/// Receives scan results as they are produced, so output can be written
+/// incrementally instead of holding a whole [`Document`] in memory
+pub trait ScanSink {
+    /// Called once per input root, before anything else from it
+    fn start(&mut self, last_updated: &str) -> TalosResult<()>;
+    fn package(&mut self, package: PackageEntry) -> TalosResult<()>;
+    /// A summarized file, with its imports already resolved
+    fn file(&mut self, location: &FileLocation, file: FileEntry) -> TalosResult<()>;
+    fn dependency(&mut self, edge: DependencyEdge) -> TalosResult<()>;
+    fn error(&mut self, error: ErrorEntry) -> TalosResult<()>;
+    fn warning(&mut self, warning: WarningEntry) -> TalosResult<()>;
+    fn skipped(&mut self, skipped: SkippedEntry) -> TalosResult<()>;
+}
+
+/// Scan each input (a directory or a single file) and merge the results into
+/// one document. With more than one input, every entry carries a `root` label:
+/// the input as given.
+pub fn scan_inputs(
+    inputs: &[PathBuf],
+    opts: &ScanOptions,
+) -> TalosResult<(Document, Vec<ErrorEntry>)> {
+    let mut merged: Option<(Document, Vec<ErrorEntry>)> = None;
+    for (input, label) in inputs.iter().zip(root_labels(inputs)?) {
+        let mut builder = DocumentBuilder::default();
+        scan_root(
+            input,
+            opts,
+            &mut Labelled {
+                label,
+                sink: &mut builder,
+            },
+        )?;
+
+        let (doc, mut errors) = builder.finish();
+        match merged.as_mut() {
+            Some((merged_doc, merged_errors)) => {
+                merged_doc.merge(doc);
+                merged_errors.append(&mut errors);
+            }
+            None => merged = Some((doc, errors)),
+        }
+    }
+    merged.ok_or_else(|| TalosError::InvalidInput("No input given".to_string()))
+}
+
+/// Scan each input like [`scan_inputs`], handing every entry to `sink` as soon
+/// as it is produced. Only the walked file list and the symbols C/C++ headers
+/// declare are held in memory.
+pub fn stream_inputs(
+    inputs: &[PathBuf],
+    opts: &ScanOptions,
+    sink: &mut dyn ScanSink,
+) -> TalosResult<()> {
+    for (input, label) in inputs.iter().zip(root_labels(inputs)?) {
+        scan_root(input, opts, &mut Labelled { label, sink })?;
+    }
+    Ok(())
+}
+
+/// Scan one directory, or a single file (summarized relative to its directory)
 pub fn scan_project(root: &Path, opts: &ScanOptions) -> TalosResult<(Document, Vec<ErrorEntry>)> {
-    let root = root.canonicalize()?;
+    let mut builder = DocumentBuilder::default();
+    scan_root(root, opts, &mut builder)?;
+    Ok(builder.finish())
+}
+
+/// Root labels for the inputs: none for a single input, otherwise the path as
+/// given, without trailing separators
+fn root_labels(inputs: &[PathBuf]) -> TalosResult<Vec<Option<String>>> {
+    if inputs.len() < 2 {
+        return Ok(vec![None; inputs.len()]);
+    }
+
+    let mut seen: BTreeSet<String> = BTreeSet::new();
+    let mut labels = Vec::new();
+    for input in inputs {
+        let label = input.to_string_lossy();
+        let trimmed = label.trim_end_matches(['/', '\\']);
+        let label = if trimmed.is_empty() {
+            label.to_string()
+        } else {
+            trimmed.to_string()
+        };
+        if !seen.insert(label.clone()) {
+            return Err(TalosError::InvalidInput(format!(
+                "'{}' is given more than once",
+                label
+            )));
+        }
+        labels.push(Some(label));
+    }
+    Ok(labels)
+}
+
+fn scan_root(input: &Path, opts: &ScanOptions, sink: &mut dyn ScanSink) -> TalosResult<()> {
+    let input = input.canonicalize()?;
+    let single_file = input.is_file();
+    let root = match input.parent() {
+        Some(parent) if single_file => parent.to_path_buf(),
+        _ => input.clone(),
+    };
     let ts = format_timestamp()?;
+    sink.start(&ts)?;
 
-    let (include_set, exclude_set) = build_globsets(&opts.include_globs, &opts.exclude_globs)?;
-    let allowed_extensions = &opts.allowed_exts;
+    let filters = build_globsets(opts)?;
+
+    // Workspaces and tsconfig files only matter when scanning a tree
+    let project_files = if single_file {
+        ProjectFiles::default()
+    } else {
+        ProjectFiles::find(&root)
+    };
+    let (mut packages, package_errors) = discover_packages(&root, &project_files);
+    for error in package_errors {
+        sink.error(error)?;
+    }
+    let walk_root = match &opts.package {
+        Some(_) if single_file => input.clone(),
+        Some(name) => {
+            if !packages.iter().any(|p| &p.name == name) {
+                return Err(unknown_package(name, &packages));
+            }
+            packages.retain(|p| &p.name == name);
+            let package = &packages[0];
+            root.join(&package.dir)
+        }
+        None => input.clone(),
+    };
+    let package_dirs: Vec<PathBuf> = packages.iter().map(|p| p.dir.clone()).collect();
+    for package in packages {
+        sink.package(package_entry(package))?;
+    }
 
     // Walk and group files by directory
     let mut by_dir: BTreeMap<PathBuf, Vec<PathBuf>> = BTreeMap::new();
 
-    let mut walker = WalkBuilder::new(&root);
+    let mut walker = WalkBuilder::new(&walk_root);
     walker
-        .hidden(false)
-        .follow_links(false)
-        .git_ignore(true)
-        .parents(true);
+        .hidden(opts.skip_hidden)
+        .follow_links(opts.follow_links)
+        .git_ignore(opts.git_ignore)
+        .git_exclude(opts.git_ignore)
+        .git_global(opts.git_ignore)
+        .max_depth(opts.max_depth)
+        .parents(true)
+        .add_custom_ignore_filename(IGNORE_FILE);
+    // Every default is `**/<dir>/**`, so skip those directories outright,
+    // remembering them for the skip report
+    let pruned: Arc<Mutex<Vec<(PathBuf, &'static str)>>> = Arc::default();
+    if opts.default_excludes {
+        let pruned = Arc::clone(&pruned);
+        let explain = opts.explain;
+        walker.filter_entry(move |e| {
+            let is_dir = e.file_type().is_some_and(|ft| ft.is_dir());
+            let Some(pattern) = is_dir
+                .then(|| default_excluded_dir(&e.file_name().to_string_lossy()))
+                .flatten()
+            else {
+                return true;
+            };
+            if explain {
+                if let Ok(mut pruned) = pruned.lock() {
+                    pruned.push((e.path().to_path_buf(), pattern));
+                }
+            }
+            false
+        });
+    }
     let walker = walker.build();
 
     for dent in walker {
         let dent = match dent {
             Ok(d) => d,
-            Err(_) => continue,
+            Err(err) => {
+                sink.error(walk_error(&err, &root))?;
+                continue;
+            }
         };
         let p = dent.path();
         if dent.file_type().map(|ft| ft.is_dir()).unwrap_or(false) {
             continue;
         }
-        if !is_allowed_file(p, allowed_extensions, opts.max_file_size.as_ref()) {
+        // Without --follow-symlinks, a link to a directory comes through as a
+        // symlink entry rather than a directory
+        if dent.path_is_symlink() && p.is_dir() {
+            if opts.explain {
+                sink.skipped(SkippedEntry {
+                    root: None,
+                    path: path_relative_to(p, &root),
+                    reason: SkipReason::Symlink,
+                    detail: "symlinked directory (pass --follow-symlinks)".to_string(),
+                })?;
+            }
             continue;
         }
-        if !included(p, include_set.as_ref(), &exclude_set) {
-            continue;
+        match skip_reason(p, &root, &filters, opts) {
+            Ok(None) => {}
+            Ok(Some(skip)) => {
+                if skip.reason == SkipReason::SizeLimit {
+                    sink.error(ErrorEntry::new(
+                        skip.path.clone(),
+                        ErrorKind::SizeLimit,
+                        skip.detail.clone(),
+                    ))?;
+                }
+                if opts.explain {
+                    sink.skipped(skip)?;
+                }
+                continue;
+            }
+            Err(e) => {
+                sink.error(ErrorEntry::from_error(path_relative_to(p, &root), &e))?;
+                continue;
+            }
         }
 
         let dir = p.parent().unwrap_or(&root).to_path_buf();
         by_dir.entry(dir).or_default().push(p.to_path_buf());
     }
 
-    let mut directories: Vec<DirectoryEntry> = Vec::new();
-    let mut errors: Vec<ErrorEntry> = Vec::new();
+    let pruned = std::mem::take(&mut *pruned.lock().unwrap_or_else(|e| e.into_inner()));
+    for (dir, pattern) in pruned {
+        sink.skipped(SkippedEntry {
+            root: None,
+            path: path_relative_to(&dir, &root),
+            reason: SkipReason::Excluded,
+            detail: format!("directory matches exclude glob `{}`", pattern),
+        })?;
+    }
+
+    // Every walked file, so imports can resolve even to files with no signatures
+    let known_files: BTreeSet<String> = by_dir
+        .values()
+        .flatten()
+        .map(|p| path_relative_to(p, &root))
+        .collect();
+
+    let resolver = if single_file {
+        ImportResolver::new(known_files)
+    } else {
+        let (resolver, resolver_errors) =
+            ImportResolver::discover(&root, &project_files, known_files);
+        for error in resolver_errors {
+            sink.error(error)?;
+        }
+        resolver
+    };
 
-    for (dir_abs, mut files) in by_dir.into_iter() {
+    let mut generated = GeneratedDetector::new(&root)?;
+
+    // Headers go first, so C/C++ sources can drop what a header already declares
+    for files in by_dir.values_mut() {
         files.sort();
-        let mut file_entries: Vec<FileEntry> = Vec::new();
-
-        for file_path in files {
-            let rel_file = path_relative_to(&file_path, &root);
-            let file_name = extract_file_name(&file_path, &rel_file);
-
-            match extract_signatures_for_file(&file_path) {
-                Ok(sigs) => {
-                    if opts.terse_output && sigs.is_empty() {
-                        continue;
-                    }
-                    file_entries.push(FileEntry {
-                        file_name,
-                        relative_file_path: rel_file,
-                        last_scanned: ts.clone(),
-                        signatures: sigs,
-                        summary: None,
-                    });
-                }
-                Err(e) => errors.push(ErrorEntry {
+    }
+    let (headers, sources): (Vec<_>, Vec<_>) = by_dir
+        .iter()
+        .flat_map(|(dir, files)| files.iter().map(move |file| (dir, file)))
+        .partition(|(_, file)| has_ext(file, C_HEADER_EXTS));
+    let mut declared: BTreeSet<CSymbol> = BTreeSet::new();
+
+    for (dir_abs, file_path) in headers.into_iter().chain(sources) {
+        let directory_path = path_relative_to(dir_abs, &root);
+        let location = FileLocation {
+            root: None,
+            package_path: owning_package(&directory_path, &package_dirs)
+                .map(|idx| package_path(&package_dirs[idx])),
+            directory_path,
+        };
+
+        let rel_file = path_relative_to(file_path, &root);
+        let file_name = extract_file_name(file_path, &rel_file);
+
+        let source = match read_source(file_path) {
+            Ok(source) => source,
+            Err(e) => {
+                sink.error(ErrorEntry::from_error(rel_file, &e))?;
+                continue;
+            }
+        };
+        let generated_by = match opts.generated {
+            GeneratedFiles::Keep => None,
+            _ => generated.detect(file_path, &source.code),
+        };
+        if let Some(detail) = &generated_by {
+            if opts.generated == GeneratedFiles::Skip {
+                // Listed even without --explain: detection is a heuristic,
+                // so a dropped file should never go unnoticed
+                sink.skipped(SkippedEntry {
+                    root: None,
                     path: rel_file,
-                    error: e.to_string(),
-                }),
+                    reason: SkipReason::Generated,
+                    detail: detail.clone(),
+                })?;
+                continue;
             }
         }
 
-        if !file_entries.is_empty() {
-            directories.push(DirectoryEntry {
-                directory_path: path_relative_to(&dir_abs, &root),
-                files: file_entries,
+        let extraction = match opts.extractors.extract_source(file_path, source) {
+            Ok(extraction) => extraction,
+            Err(e) => {
+                sink.error(ErrorEntry::from_error(rel_file, &e))?;
+                continue;
+            }
+        };
+        let partial = !extraction.diagnostics.is_empty();
+        for d in extraction.diagnostics {
+            sink.warning(WarningEntry {
+                root: None,
+                path: rel_file.clone(),
+                line: d.line,
+                column: d.column,
+                message: d.message,
+            })?;
+        }
+        let mut signatures = extraction.signatures;
+        if has_ext(file_path, C_HEADER_EXTS) {
+            declared.extend(signatures.iter().filter_map(|sig| declared_symbol(sig)));
+        } else if has_ext(file_path, C_SOURCE_EXTS) && !declared.is_empty() {
+            signatures.retain(|sig| {
+                declared_symbol(sig).is_none_or(|symbol| !declared_in_headers(&symbol, &declared))
             });
         }
+        if opts.terse_output && signatures.is_empty() {
+            continue;
+        }
+        let mut entry = FileEntry {
+            file_name,
+            relative_file_path: rel_file,
+            last_scanned: ts.clone(),
+            signatures,
+            imports: extraction
+                .imports
+                .into_iter()
+                .map(|specifier| ImportEntry {
+                    specifier,
+                    resolved_path: None,
+                })
+                .collect(),
+            components: extraction.components,
+            summary: None,
+            partial,
+            encoding: extraction.encoding,
+            generated: generated_by.is_some(),
+            definitions: if opts.definitions {
+                extraction.definitions
+            } else {
+                Vec::new()
+            },
+        };
+        let edges = link_file(&mut entry, &resolver);
+        sink.file(&location, entry)?;
+        for edge in edges {
+            sink.dependency(edge)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Collects scan results into a [`Document`]
+#[derive(Default)]
+struct DocumentBuilder {
+    last_updated: Option<String>,
+    directories: Vec<DirectoryEntry>,
+    directory_index: HashMap<String, usize>,
+    packages: Vec<PackageEntry>,
+    dependencies: Vec<DependencyEdge>,
+    errors: Vec<ErrorEntry>,
+    warnings: Vec<WarningEntry>,
+    skipped: Vec<SkippedEntry>,
+}
+
+impl DocumentBuilder {
+    fn finish(self) -> (Document, Vec<ErrorEntry>) {
+        let mut directories = self.directories;
+        let mut dependencies = self.dependencies;
+        let mut skipped = self.skipped;
+
+        // Deterministic ordering
+        directories.sort_by(|a, b| a.directory_path.cmp(&b.directory_path));
+        for dir in &mut directories {
+            dir.files
+                .sort_by(|a, b| a.relative_file_path.cmp(&b.relative_file_path));
+        }
+        dependencies.sort_by(|a, b| (&a.from, &a.to).cmp(&(&b.from, &b.to)));
+        skipped.sort_by(|a, b| a.path.cmp(&b.path));
+        let (directories, packages) = group_by_package(directories, self.packages);
+
+        let doc = Document {
+            schema_version: SCHEMA_VERSION.to_string(),
+            last_updated: self.last_updated.unwrap_or_default(),
+            directories,
+            packages,
+            dependencies,
+            errors: Vec::new(),
+            warnings: self.warnings,
+            skipped,
+        };
+        (doc, self.errors)
+    }
+}
+
+impl ScanSink for DocumentBuilder {
+    fn start(&mut self, last_updated: &str) -> TalosResult<()> {
+        self.last_updated
+            .get_or_insert_with(|| last_updated.to_string());
+        Ok(())
+    }
+
+    fn package(&mut self, package: PackageEntry) -> TalosResult<()> {
+        self.packages.push(package);
+        Ok(())
+    }
+
+    fn file(&mut self, location: &FileLocation, file: FileEntry) -> TalosResult<()> {
+        // Headers are scanned first, so a directory can come up twice
+        match self.directory_index.get(&location.directory_path) {
+            Some(&idx) => self.directories[idx].files.push(file),
+            None => {
+                self.directory_index
+                    .insert(location.directory_path.clone(), self.directories.len());
+                self.directories.push(DirectoryEntry {
+                    root: location.root.clone(),
+                    directory_path: location.directory_path.clone(),
+                    files: vec![file],
+                });
+            }
+        }
+        Ok(())
+    }
+
+    fn dependency(&mut self, edge: DependencyEdge) -> TalosResult<()> {
+        self.dependencies.push(edge);
+        Ok(())
+    }
+
+    fn error(&mut self, error: ErrorEntry) -> TalosResult<()> {
+        self.errors.push(error);
+        Ok(())
+    }
+
+    fn warning(&mut self, warning: WarningEntry) -> TalosResult<()> {
+        self.warnings.push(warning);
+        Ok(())
+    }
+
+    fn skipped(&mut self, skipped: SkippedEntry) -> TalosResult<()> {
+        self.skipped.push(skipped);
+        Ok(())
+    }
+}
+
+/// Stamps every entry with its input's root label before passing it on
+struct Labelled<'a> {
+    label: Option<String>,
+    sink: &'a mut dyn ScanSink,
+}
+
+impl ScanSink for Labelled<'_> {
+    fn start(&mut self, last_updated: &str) -> TalosResult<()> {
+        self.sink.start(last_updated)
+    }
+
+    fn package(&mut self, package: PackageEntry) -> TalosResult<()> {
+        self.sink.package(PackageEntry {
+            root: self.label.clone(),
+            ..package
+        })
+    }
+
+    fn file(&mut self, location: &FileLocation, file: FileEntry) -> TalosResult<()> {
+        let location = FileLocation {
+            root: self.label.clone(),
+            ..location.clone()
+        };
+        self.sink.file(&location, file)
+    }
+
+    fn dependency(&mut self, edge: DependencyEdge) -> TalosResult<()> {
+        self.sink.dependency(DependencyEdge {
+            root: self.label.clone(),
+            ..edge
+        })
+    }
+
+    fn error(&mut self, error: ErrorEntry) -> TalosResult<()> {
+        self.sink.error(ErrorEntry {
+            root: self.label.clone(),
+            ..error
+        })
+    }
+
+    fn warning(&mut self, warning: WarningEntry) -> TalosResult<()> {
+        self.sink.warning(WarningEntry {
+            root: self.label.clone(),
+            ..warning
+        })
+    }
+
+    fn skipped(&mut self, skipped: SkippedEntry) -> TalosResult<()> {
+        self.sink.skipped(SkippedEntry {
+            root: self.label.clone(),
+            ..skipped
+        })
+    }
+}
+
+fn package_entry(package: Package) -> PackageEntry {
+    PackageEntry {
+        root: None,
+        package_path: package_path(&package.dir),
+        name: package.name,
+        version: package.version,
+        kind: package.kind,
+        entry_points: package.entry_points,
+        directories: Vec::new(),
+    }
+}
+
+/// `package_path` for a package directory: `.` for the root itself
+fn package_path(dir: &Path) -> String {
+    if dir.as_os_str().is_empty() {
+        ".".to_string()
+    } else {
+        dir.to_string_lossy().to_string()
+    }
+}
+
+/// Index of the deepest package directory containing `directory_path`
+fn owning_package(directory_path: &str, package_dirs: &[PathBuf]) -> Option<usize> {
+    let path = Path::new(directory_path);
+    package_dirs
+        .iter()
+        .enumerate()
+        .filter(|(_, pkg_dir)| path.starts_with(pkg_dir))
+        .max_by_key(|(_, pkg_dir)| pkg_dir.components().count())
+        .map(|(idx, _)| idx)
+}
+
+/// Move each directory into the deepest package containing it. Directories
+/// outside every package are returned first.
+fn group_by_package(
+    directories: Vec<DirectoryEntry>,
+    mut packages: Vec<PackageEntry>,
+) -> (Vec<DirectoryEntry>, Vec<PackageEntry>) {
+    let package_dirs: Vec<PathBuf> = packages
+        .iter()
+        .map(|p| match p.package_path.as_str() {
+            "." => PathBuf::new(),
+            path => PathBuf::from(path),
+        })
+        .collect();
+
+    let mut rest = Vec::new();
+    for dir in directories {
+        match owning_package(&dir.directory_path, &package_dirs) {
+            Some(idx) => packages[idx].directories.push(dir),
+            None => rest.push(dir),
+        }
     }
+    (rest, packages)
+}
 
-    // Deterministic ordering
-    directories.sort_by(|a, b| a.directory_path.cmp(&b.directory_path));
+fn unknown_package(name: &str, packages: &[Package]) -> TalosError {
+    let names: Vec<&str> = packages.iter().map(|p| p.name.as_str()).collect();
+    let known = if names.is_empty() {
+        "no workspace packages were found".to_string()
+    } else {
+        format!("known packages: {}", names.join(", "))
+    };
+    TalosError::InvalidInput(format!("No workspace package named '{}' ({})", name, known))
+}
+
+/// Explain whether `path` (a file under `root`) would be summarized: `None` if
+/// it would be, otherwise the filter that leaves it out. Mirrors the checks made
+/// while walking, including ignore files.
+pub fn explain_path(
+    root: &Path,
+    path: &Path,
+    opts: &ScanOptions,
+) -> TalosResult<Option<SkippedEntry>> {
+    let root = root.canonicalize()?;
+    let path = absolute_under(&root, path)?;
+    if !path.starts_with(&root) {
+        return Err(TalosError::InvalidInput(format!(
+            "'{}' is not under '{}'",
+            path.display(),
+            root.display()
+        )));
+    }
+    if path.is_dir() {
+        return Err(TalosError::InvalidInput(format!(
+            "'{}' is a directory",
+            path.display()
+        )));
+    }
 
-    let doc = crate::model::Document {
-        schema_version: "1.0".to_string(),
-        last_updated: ts,
-        directories,
-        errors: Vec::new(),
+    let skipped = |reason, detail: String| {
+        Ok(Some(SkippedEntry {
+            root: None,
+            path: path_relative_to(&path, &root),
+            reason,
+            detail,
+        }))
     };
+    let rel_path = path.strip_prefix(&root).unwrap_or(&path);
+
+    if let Some(link) = symlinked_dir(&root, &path).filter(|_| !opts.follow_links) {
+        return skipped(
+            SkipReason::Symlink,
+            format!(
+                "inside symlinked directory `{}` (pass --follow-symlinks)",
+                path_relative_to(&link, &root)
+            ),
+        );
+    }
+    if let Some(max) = opts
+        .max_depth
+        .filter(|max| rel_path.components().count() > *max)
+    {
+        return skipped(
+            SkipReason::Depth,
+            format!("deeper than --max-depth {}", max),
+        );
+    }
+    let hidden = rel_path
+        .components()
+        .find(|c| c.as_os_str().to_string_lossy().starts_with('.'));
+    if let Some(hidden) = hidden.filter(|_| opts.skip_hidden) {
+        return skipped(
+            SkipReason::Hidden,
+            format!(
+                "`{}` is hidden (--no-hidden)",
+                hidden.as_os_str().to_string_lossy()
+            ),
+        );
+    }
+    if let Some(rule) = ignore_rule(&root, &path, opts.git_ignore) {
+        return skipped(SkipReason::Ignored, rule);
+    }
+
+    let filters = build_globsets(opts)?;
+    if let Some(skip) = skip_reason(&path, &root, &filters, opts)? {
+        return Ok(Some(skip));
+    }
+
+    if opts.generated != GeneratedFiles::Skip {
+        return Ok(None);
+    }
+    let source = read_source(&path)?;
+    let detail = GeneratedDetector::new(&root)?.detect(&path, &source.code);
+    Ok(detail.map(|detail| SkippedEntry {
+        root: None,
+        path: path_relative_to(&path, &root),
+        reason: SkipReason::Generated,
+        detail,
+    }))
+}
+
+/// The first filter that leaves `path` out, in walk order: globs, extension,
+/// size. Generated files are recognised later, from their content.
+fn skip_reason(
+    path: &Path,
+    root: &Path,
+    filters: &Filters,
+    opts: &ScanOptions,
+) -> TalosResult<Option<SkippedEntry>> {
+    let skipped = |reason, detail: String| {
+        Ok(Some(SkippedEntry {
+            root: None,
+            path: path_relative_to(path, root),
+            reason,
+            detail,
+        }))
+    };
+
+    let rel_path = path.strip_prefix(root).unwrap_or(path);
+    if let Some(pattern) = filters.matching_exclude(rel_path) {
+        return skipped(
+            SkipReason::Excluded,
+            format!("matches exclude glob `{}`", pattern),
+        );
+    }
+    if !filters.is_included(rel_path) {
+        return skipped(
+            SkipReason::NotIncluded,
+            format!(
+                "matches none of the include globs ({})",
+                filters.include_patterns.join(", ")
+            ),
+        );
+    }
+    if !is_allowed_file(path, &opts.allowed_exts, &opts.extractors) {
+        let detail = match path.extension() {
+            Some(ext) => format!(
+                "extension `.{}` is not in the allowed list",
+                ext.to_string_lossy()
+            ),
+            None => "no extension, and no recognised `#!` line or modeline".to_string(),
+        };
+        return skipped(SkipReason::Extension, detail);
+    }
+    if let Some(max) = opts.max_file_size.as_ref() {
+        let len = fs::metadata(path)?.len();
+        if max.exceeds(len) {
+            return skipped(
+                SkipReason::SizeLimit,
+                format!("File is {} bytes, over the {} byte limit", len, max.bytes()),
+            );
+        }
+    }
+    Ok(None)
+}
 
-    Ok((doc, errors))
+/// `path` made absolute without resolving symlinks, so files reached through a
+/// symlinked directory stay under `root`; falls back to the canonical path
+fn absolute_under(root: &Path, path: &Path) -> TalosResult<PathBuf> {
+    let cwd = std::env::current_dir()?.canonicalize()?;
+    let mut lexical = PathBuf::new();
+    for component in cwd.join(path).components() {
+        match component {
+            std::path::Component::CurDir => {}
+            std::path::Component::ParentDir => {
+                lexical.pop();
+            }
+            other => lexical.push(other),
+        }
+    }
+    if lexical.starts_with(root) && lexical.exists() {
+        return Ok(lexical);
+    }
+    Ok(path.canonicalize()?)
+}
+
+/// Outermost symlinked directory between `root` and `path`
+fn symlinked_dir(root: &Path, path: &Path) -> Option<PathBuf> {
+    let mut dirs: Vec<&Path> = path
+        .parent()?
+        .ancestors()
+        .take_while(|dir| dir.starts_with(root) && *dir != root)
+        .collect();
+    dirs.reverse();
+    dirs.into_iter()
+        .find(|dir| {
+            dir.symlink_metadata()
+                .is_ok_and(|m| m.file_type().is_symlink())
+        })
+        .map(Path::to_path_buf)
+}
+
+/// Ignore-file rule matching `path`, checking the global gitignore and `.git/info/exclude`, then
+/// `.gitignore`, `.ignore` and `.talosignore` files from the repository (or scan) root down,
+/// with deeper files taking precedence as in the walker
+fn ignore_rule(root: &Path, path: &Path, git_ignore: bool) -> Option<String> {
+    let repo_root = root
+        .ancestors()
+        .find(|dir| dir.join(".git").exists())
+        .filter(|_| git_ignore);
+    let top = repo_root.unwrap_or(root);
+
+    let mut verdict = None;
+    let mut ignore_files: Vec<PathBuf> = Vec::new();
+    if let Some(repo) = repo_root {
+        let (global, _) = GitignoreBuilder::new(top).build_global();
+        if let Match::Ignore(glob) = global.matched_path_or_any_parents(path, false) {
+            let source = glob
+                .from()
+                .map(|from| from.display().to_string())
+                .unwrap_or_else(|| "global gitignore".to_string());
+            verdict = Some(format!("{} rule `{}`", source, glob.original()));
+        }
+        ignore_files.push(repo.join(".git").join("info").join("exclude"));
+    }
+    let mut dirs: Vec<&Path> = path
+        .parent()?
+        .ancestors()
+        .take_while(|dir| dir.starts_with(top))
+        .collect();
+    dirs.reverse();
+    for dir in dirs {
+        if repo_root.is_some() {
+            ignore_files.push(dir.join(".gitignore"));
+        }
+        ignore_files.push(dir.join(".ignore"));
+        ignore_files.push(dir.join(IGNORE_FILE));
+    }
+
+    for file in ignore_files.iter().filter(|f| f.is_file()) {
+        let matcher_root = if file.ends_with("info/exclude") {
+            top
+        } else {
+            file.parent().unwrap_or(top)
+        };
+        let mut builder = GitignoreBuilder::new(matcher_root);
+        builder.add(file);
+        let Ok(matcher) = builder.build() else {
+            continue;
+        };
+        match matcher.matched_path_or_any_parents(path, false) {
+            Match::Ignore(glob) => {
+                let source = path_relative_to(file, top);
+                verdict = Some(format!("{} rule `{}`", source, glob.original()));
+            }
+            Match::Whitelist(_) => verdict = None,
+            Match::None => {}
+        }
+    }
+    verdict
+}
+
+const C_HEADER_EXTS: &[&str] = &["h", "hh", "hpp", "hxx"];
+const C_SOURCE_EXTS: &[&str] = &["c", "cc", "cpp", "cxx"];
+
+fn has_ext(path: &Path, exts: &[&str]) -> bool {
+    path.extension()
+        .is_some_and(|e| exts.contains(&e.to_string_lossy().to_ascii_lowercase().as_str()))
+}
+
+/// Whether a header already declares `symbol`: the same function, type, macro
+/// or namespace. A qualified definition is covered by its class, or by a
+/// function of that name (declared inside a namespace block).
+fn declared_in_headers(symbol: &CSymbol, declared: &BTreeSet<CSymbol>) -> bool {
+    match symbol {
+        CSymbol::Qualified { owner, name } => {
+            declared.contains(&CSymbol::Type(owner.clone()))
+                || declared.contains(&CSymbol::Function(name.clone()))
+        }
+        symbol => declared.contains(symbol),
+    }
 }
 
 fn format_timestamp() -> TalosResult<String> {
@@ -132,20 +902,57 @@ fn extract_file_name(file_path: &Path, rel_file: &str) -> String {
         .unwrap_or_else(|| rel_file.to_string())
 }
 
-fn is_allowed_file(path: &Path, extensions: &Extensions, max_size: Option<&MaxFileSize>) -> bool {
-    if !matches_allowed_ext(path, extensions) {
-        return false;
+fn is_allowed_file(path: &Path, extensions: &Extensions, extractors: &ExtractorRegistry) -> bool {
+    // Registered names count as their language (`Gemfile` as `rb`), and
+    // extensionless scripts are sniffed (`#!/usr/bin/env node` counts as `js`)
+    matches_allowed_ext(path, extensions)
+        || extractors
+            .file_name_extensions(path)
+            .iter()
+            .any(|ext| extensions.contains(ext))
+        || extractors
+            .sniff_file(path)
+            .is_some_and(|key| extensions.contains(&key))
+}
+
+/// Walker failure as an error entry: unreadable directories, bad ignore files,
+/// symlink loops
+fn walk_error(err: &ignore::Error, root: &Path) -> ErrorEntry {
+    let path = error_path(err)
+        .map(|p| path_relative_to(p, root))
+        .unwrap_or_else(|| ".".to_string());
+    let kind = match err.io_error().map(|e| e.kind()) {
+        Some(std::io::ErrorKind::PermissionDenied) => ErrorKind::Permission,
+        _ => ErrorKind::Walk,
+    };
+    ErrorEntry {
+        line: error_line(err),
+        ..ErrorEntry::new(path, kind, err.to_string())
     }
+}
 
-    if let Some(max) = max_size {
-        if let Ok(metadata) = fs::metadata(path) {
-            if max.exceeds(metadata.len()) {
-                return false;
-            }
+fn error_path(err: &ignore::Error) -> Option<&Path> {
+    match err {
+        ignore::Error::WithPath { path, .. } => Some(path),
+        ignore::Error::Loop { child, .. } => Some(child),
+        ignore::Error::WithDepth { err, .. } | ignore::Error::WithLineNumber { err, .. } => {
+            error_path(err)
         }
+        ignore::Error::Partial(errs) => errs.iter().find_map(error_path),
+        _ => None,
     }
+}
 
-    true
+/// Line number within an ignore file, for malformed patterns
+fn error_line(err: &ignore::Error) -> Option<usize> {
+    match err {
+        ignore::Error::WithLineNumber { line, .. } => Some(*line as usize),
+        ignore::Error::WithPath { err, .. } | ignore::Error::WithDepth { err, .. } => {
+            error_line(err)
+        }
+        ignore::Error::Partial(errs) => errs.iter().find_map(error_line),
+        _ => None,
+    }
 }
 
 fn matches_allowed_ext(path: &Path, extensions: &Extensions) -> bool {
@@ -156,72 +963,304 @@ fn matches_allowed_ext(path: &Path, extensions: &Extensions) -> bool {
     extensions.contains(&ext)
 }
 
-fn build_globsets(
-    includes: &GlobPatterns,
-    excludes: &GlobPatterns,
-) -> TalosResult<(Option<GlobSet>, GlobSet)> {
-    let include_set = build_include_globset(includes)?;
-    let exclude_set = build_exclude_globset(excludes)?;
-    Ok((include_set, exclude_set))
+/// Include/exclude globs plus the patterns behind them, for explanations.
+/// Patterns match the path relative to the scan root, with `/` separators:
+/// `src/**` is the top-level `src` directory, `**/fixtures/**` any `fixtures`.
+struct Filters {
+    include: Option<GlobSet>,
+    include_patterns: Vec<String>,
+    exclude: GlobSet,
+    /// Default excludes (unless disabled) first, then user excludes, in `exclude` index order
+    exclude_patterns: Vec<String>,
 }
 
-fn build_include_globset(includes: &GlobPatterns) -> TalosResult<Option<GlobSet>> {
-    if includes.is_empty() {
-        return Ok(None);
+impl Filters {
+    fn matching_exclude(&self, rel_path: &Path) -> Option<&str> {
+        let idx = *self.exclude.matches(rel_path).first()?;
+        self.exclude_patterns.get(idx).map(String::as_str)
+    }
+
+    fn is_included(&self, rel_path: &Path) -> bool {
+        self.include.as_ref().is_none_or(|gs| gs.is_match(rel_path))
     }
+}
+
+fn build_globsets(opts: &ScanOptions) -> TalosResult<Filters> {
+    let non_empty = |patterns: &GlobPatterns| -> Vec<String> {
+        patterns
+            .iter()
+            .filter(|p| !p.trim().is_empty())
+            .cloned()
+            .collect()
+    };
+    let include_patterns = non_empty(&opts.include_globs);
+    let mut exclude_patterns: Vec<String> = Vec::new();
+    if opts.default_excludes {
+        exclude_patterns.extend(DEFAULT_EXCLUDES.iter().map(|p| p.to_string()));
+    }
+    exclude_patterns.extend(non_empty(&opts.exclude_globs));
+
+    let include = if include_patterns.is_empty() {
+        None
+    } else {
+        Some(build_globset(&include_patterns, "include")?)
+    };
+
+    Ok(Filters {
+        include,
+        include_patterns,
+        exclude: build_globset(&exclude_patterns, "exclude")?,
+        exclude_patterns,
+    })
+}
 
+/// Patterns are matched against root-relative paths like `.gitignore` lines:
+/// one without a `/` matches the file name at any depth, one with a `/` is
+/// anchored at the root (a leading `/` or `./` is dropped)
+fn build_globset(patterns: &[String], kind: &str) -> TalosResult<GlobSet> {
     let mut builder = GlobSetBuilder::new();
-    for pattern in includes.iter() {
-        if pattern.trim().is_empty() {
-            continue;
-        }
-        let glob = Glob::new(pattern).map_err(|e| {
-            TalosError::ScanError(format!("Invalid include glob '{}': {}", pattern, e))
-        })?;
+    for pattern in patterns {
+        let glob = if pattern.contains('/') {
+            pattern
+                .trim_start_matches("./")
+                .trim_start_matches('/')
+                .to_string()
+        } else {
+            format!("**/{}", pattern)
+        };
+        let glob = GlobBuilder::new(&glob)
+            .literal_separator(true)
+            .build()
+            .map_err(|e| {
+                TalosError::ScanError(format!("Invalid {} glob '{}': {}", kind, pattern, e))
+            })?;
         builder.add(glob);
     }
 
-    let globset = builder
+    builder
         .build()
-        .map_err(|e| TalosError::ScanError(format!("Failed to build include globset: {}", e)))?;
-    Ok(Some(globset))
+        .map_err(|e| TalosError::ScanError(format!("Failed to build {} globset: {}", kind, e)))
 }
 
-fn build_exclude_globset(excludes: &GlobPatterns) -> TalosResult<GlobSet> {
-    let mut builder = GlobSetBuilder::new();
+/// Excluded unless `--no-default-excludes` is given
+pub const DEFAULT_EXCLUDES: &[&str] = &[
+    "**/node_modules/**",
+    "**/dist/**",
+    "**/.git/**",
+    "**/coverage/**",
+];
 
-    // Add sensible defaults
-    for pattern in DEFAULT_EXCLUDES {
-        let glob = Glob::new(pattern).map_err(|e| {
-            TalosError::ScanError(format!("Invalid default exclude pattern: {}", e))
-        })?;
-        builder.add(glob);
+/// The default exclude pruning directory `name`, if any
+fn default_excluded_dir(name: &str) -> Option<&'static str> {
+    DEFAULT_EXCLUDES.iter().copied().find(|pattern| {
+        pattern
+            .strip_prefix("**/")
+            .and_then(|p| p.strip_suffix("/**"))
+            == Some(name)
+    })
+}
+
+/// Per-directory ignore file in gitignore syntax, honoured like `.gitignore`
+pub const IGNORE_FILE: &str = ".talosignore";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::OutputPath;
+    use crate::writer::NdjsonWriter;
+
+    fn filters(include: &[&str], exclude: &[&str]) -> Filters {
+        let include_patterns: Vec<String> = include.iter().map(|p| p.to_string()).collect();
+        let exclude_patterns: Vec<String> = exclude.iter().map(|p| p.to_string()).collect();
+        Filters {
+            include: (!include.is_empty())
+                .then(|| build_globset(&include_patterns, "include").unwrap()),
+            include_patterns,
+            exclude: build_globset(&exclude_patterns, "exclude").unwrap(),
+            exclude_patterns,
+        }
     }
 
-    // Add user-specified excludes
-    for pattern in excludes.iter() {
-        if pattern.trim().is_empty() {
-            continue;
+    /// Scan options as the CLI builds them with no flags, for every built-in language
+    fn options() -> ScanOptions {
+        let extractors = ExtractorRegistry::with_builtins(&Default::default());
+        ScanOptions {
+            allowed_exts: Extensions::new(extractors.extensions()),
+            include_globs: GlobPatterns::empty(),
+            exclude_globs: GlobPatterns::empty(),
+            default_excludes: true,
+            follow_links: false,
+            skip_hidden: false,
+            git_ignore: true,
+            max_depth: None,
+            max_file_size: None,
+            terse_output: false,
+            explain: false,
+            generated: GeneratedFiles::default(),
+            package: None,
+            definitions: false,
+            extractors,
         }
-        let glob = Glob::new(pattern).map_err(|e| {
-            TalosError::ScanError(format!("Invalid exclude glob '{}': {}", pattern, e))
-        })?;
-        builder.add(glob);
     }
 
-    builder
-        .build()
-        .map_err(|e| TalosError::ScanError(format!("Failed to build exclude globset: {}", e)))
-}
+    fn write(root: &Path, rel: &str, contents: &str) {
+        let path = root.join(rel);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+
+    fn scanned_files(doc: &Document) -> Vec<&str> {
+        doc.files().map(|f| f.relative_file_path.as_str()).collect()
+    }
+
+    fn excluded_by<'a>(filters: &'a Filters, path: &str) -> Option<&'a str> {
+        filters.matching_exclude(Path::new(path))
+    }
+
+    #[test]
+    fn patterns_without_a_slash_match_at_any_depth() {
+        let f = filters(&[], &["*.test.js"]);
+        assert_eq!(excluded_by(&f, "a.test.js"), Some("*.test.js"));
+        assert_eq!(excluded_by(&f, "src/deep/a.test.js"), Some("*.test.js"));
+        assert_eq!(excluded_by(&f, "src/a.js"), None);
+    }
+
+    #[test]
+    fn patterns_with_a_slash_are_anchored() {
+        let f = filters(&["src/*.js"], &["/lib/*.js", "./tmp/*.js"]);
+        assert!(f.is_included(Path::new("src/a.js")));
+        assert!(!f.is_included(Path::new("pkg/src/a.js")));
+        // `*` does not cross directories
+        assert!(!f.is_included(Path::new("src/nested/a.js")));
 
-const DEFAULT_EXCLUDES: &[&str] = &["node_modules/**", "dist/**", ".git/**", "coverage/**"];
+        assert_eq!(excluded_by(&f, "lib/a.js"), Some("/lib/*.js"));
+        assert_eq!(excluded_by(&f, "pkg/lib/a.js"), None);
+        assert_eq!(excluded_by(&f, "tmp/a.js"), Some("./tmp/*.js"));
+    }
+
+    #[test]
+    fn double_star_crosses_directories() {
+        let f = filters(&["src/**"], &["**/__tests__/**", "**/*.min.js"]);
+        assert!(f.is_included(Path::new("src/a/b/c.ts")));
+        assert!(!f.is_included(Path::new("lib/src/c.ts")));
+
+        assert_eq!(
+            excluded_by(&f, "src/__tests__/a.ts"),
+            Some("**/__tests__/**")
+        );
+        assert_eq!(excluded_by(&f, "__tests__/a.ts"), Some("**/__tests__/**"));
+        assert_eq!(excluded_by(&f, "vendor/jq.min.js"), Some("**/*.min.js"));
+        assert_eq!(excluded_by(&f, "jq.min.js"), Some("**/*.min.js"));
+    }
 
-fn included(path: &Path, includes: Option<&GlobSet>, excludes: &GlobSet) -> bool {
-    if excludes.is_match(path) {
-        return false;
+    #[test]
+    fn default_excludes_name_whole_directories() {
+        assert_eq!(
+            default_excluded_dir("node_modules"),
+            Some("**/node_modules/**")
+        );
+        assert_eq!(default_excluded_dir("coverage"), Some("**/coverage/**"));
+        assert_eq!(default_excluded_dir("src"), None);
     }
-    if let Some(gs) = includes {
-        return gs.is_match(path);
+
+    #[test]
+    fn explain_lists_default_excluded_directories() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        write(root, "src/a.js", "export function a() {}\n");
+        write(root, "node_modules/x.js", "export function x() {}\n");
+        write(root, "packages/ui/dist/ui.js", "export function ui() {}\n");
+
+        let opts = ScanOptions {
+            explain: true,
+            ..options()
+        };
+        let (doc, _) = scan_project(root, &opts).unwrap();
+        assert_eq!(scanned_files(&doc), vec!["src/a.js"]);
+
+        let mut skipped: Vec<(&str, SkipReason, &str)> = doc
+            .skipped
+            .iter()
+            .map(|s| (s.path.as_str(), s.reason, s.detail.as_str()))
+            .collect();
+        skipped.sort_by_key(|(path, ..)| *path);
+        assert_eq!(
+            skipped,
+            vec![
+                (
+                    "node_modules",
+                    SkipReason::Excluded,
+                    "directory matches exclude glob `**/node_modules/**`"
+                ),
+                (
+                    "packages/ui/dist",
+                    SkipReason::Excluded,
+                    "directory matches exclude glob `**/dist/**`"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn registered_file_names_follow_the_allowed_extensions() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        write(root, "Gemfile", "source 'https://rubygems.org'\n");
+        write(root, "Rakefile", "task :default\n");
+        write(root, "app.ts", "export function app() {}\n");
+
+        let only = |exts: &[&str]| ScanOptions {
+            allowed_exts: Extensions::new(exts.iter().map(|e| e.to_string()).collect()),
+            ..options()
+        };
+        let (doc, _) = scan_project(root, &only(&["ts"])).unwrap();
+        assert_eq!(scanned_files(&doc), vec!["app.ts"]);
+
+        let (doc, _) = scan_project(root, &only(&["rb"])).unwrap();
+        assert_eq!(scanned_files(&doc), vec!["Gemfile", "Rakefile"]);
+    }
+
+    #[test]
+    fn streaming_writes_one_record_per_line_and_a_summary_last() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("project");
+        write(&root, "src/a.ts", "export function a() {}\n");
+        write(
+            &root,
+            "src/b.ts",
+            "import { a } from './a';\nexport class B {}\n",
+        );
+        write(&root, "lib/c.js", "function c( {\n");
+        let output = dir.path().join("out/talos.ndjson");
+
+        let mut writer = NdjsonWriter::create(&OutputPath::File(output.clone())).unwrap();
+        stream_inputs(&[root], &options(), &mut writer).unwrap();
+        writer.finish().unwrap();
+
+        let text = fs::read_to_string(&output).unwrap();
+        let records: Vec<serde_json::Value> = text
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        let types: Vec<&str> = records
+            .iter()
+            .map(|r| r["type"].as_str().unwrap())
+            .collect();
+        assert_eq!(types.first(), Some(&"header"));
+        assert_eq!(types.iter().filter(|t| **t == "file").count(), 3);
+        assert_eq!(types.iter().filter(|t| **t == "summary").count(), 1);
+        assert_eq!(types.last(), Some(&"summary"));
+
+        let summary = records.last().unwrap();
+        assert_eq!(summary["files"], 3);
+        assert_eq!(
+            summary["warnings"],
+            types.iter().filter(|t| **t == "warning").count()
+        );
+        assert!(summary["warnings"].as_u64().unwrap() > 0);
+
+        // Only the target is left behind, no temp file
+        let written: Vec<_> = fs::read_dir(output.parent().unwrap()).unwrap().collect();
+        assert_eq!(written.len(), 1);
     }
-    true
 }
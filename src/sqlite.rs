@@ -0,0 +1,287 @@
+use crate::error::{TalosError, TalosResult};
+use crate::model::{Document, PackageEntry};
+use crate::writer::DocumentWriter;
+use rusqlite::{params, Connection, DatabaseName};
+
+/// One row per directory, file, signature and import, keyed by integer ids.
+/// `root` is only set when several inputs were scanned.
+const SCHEMA: &str = "
+CREATE TABLE meta (
+    key TEXT PRIMARY KEY,
+    value TEXT NOT NULL
+);
+CREATE TABLE packages (
+    id INTEGER PRIMARY KEY,
+    root TEXT,
+    name TEXT NOT NULL,
+    version TEXT,
+    kind TEXT NOT NULL,
+    path TEXT NOT NULL
+);
+CREATE TABLE directories (
+    id INTEGER PRIMARY KEY,
+    root TEXT,
+    path TEXT NOT NULL,
+    package_id INTEGER REFERENCES packages(id)
+);
+CREATE TABLE files (
+    id INTEGER PRIMARY KEY,
+    directory_id INTEGER NOT NULL REFERENCES directories(id),
+    name TEXT NOT NULL,
+    path TEXT NOT NULL,
+    last_scanned TEXT NOT NULL,
+    summary TEXT,
+    partial INTEGER NOT NULL,
+    generated INTEGER NOT NULL,
+    encoding TEXT
+);
+CREATE TABLE signatures (
+    id INTEGER PRIMARY KEY,
+    file_id INTEGER NOT NULL REFERENCES files(id),
+    position INTEGER NOT NULL,
+    signature TEXT NOT NULL
+);
+CREATE TABLE imports (
+    id INTEGER PRIMARY KEY,
+    file_id INTEGER NOT NULL REFERENCES files(id),
+    specifier TEXT NOT NULL,
+    resolved_path TEXT
+);
+CREATE TABLE dependencies (
+    root TEXT,
+    from_path TEXT NOT NULL,
+    to_path TEXT NOT NULL
+);
+CREATE TABLE errors (
+    root TEXT,
+    path TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    error TEXT NOT NULL,
+    line INTEGER,
+    column INTEGER
+);
+CREATE TABLE warnings (
+    root TEXT,
+    path TEXT NOT NULL,
+    line INTEGER NOT NULL,
+    column INTEGER NOT NULL,
+    message TEXT NOT NULL
+);
+CREATE TABLE skipped (
+    root TEXT,
+    path TEXT NOT NULL,
+    reason TEXT NOT NULL,
+    detail TEXT NOT NULL
+);
+CREATE INDEX files_path ON files(path);
+CREATE INDEX signatures_file ON signatures(file_id);
+CREATE INDEX imports_file ON imports(file_id);
+";
+
+/// A SQLite database built in memory and written out as one file image
+pub struct SqliteWriter;
+
+impl DocumentWriter for SqliteWriter {
+    fn render(&self, doc: &Document) -> TalosResult<Vec<u8>> {
+        let conn = export(doc)?;
+        let data = conn.serialize(DatabaseName::Main).map_err(sqlite_error)?;
+        Ok(data.to_vec())
+    }
+}
+
+/// The document loaded into a fresh in-memory database
+fn export(doc: &Document) -> TalosResult<Connection> {
+    let mut conn = Connection::open_in_memory().map_err(sqlite_error)?;
+    conn.execute_batch(SCHEMA).map_err(sqlite_error)?;
+
+    let tx = conn.transaction().map_err(sqlite_error)?;
+    insert_document(&tx, doc).map_err(sqlite_error)?;
+    tx.commit().map_err(sqlite_error)?;
+    Ok(conn)
+}
+
+fn insert_document(conn: &Connection, doc: &Document) -> rusqlite::Result<()> {
+    let mut meta = conn.prepare("INSERT INTO meta (key, value) VALUES (?1, ?2)")?;
+    meta.execute(params!["schema_version", doc.schema_version])?;
+    meta.execute(params!["last_updated", doc.last_updated])?;
+
+    let mut package = conn.prepare(
+        "INSERT INTO packages (root, name, version, kind, path) VALUES (?1, ?2, ?3, ?4, ?5)",
+    )?;
+    let mut directory =
+        conn.prepare("INSERT INTO directories (root, path, package_id) VALUES (?1, ?2, ?3)")?;
+    let mut file = conn.prepare(
+        "INSERT INTO files (directory_id, name, path, last_scanned, summary, partial, generated, encoding)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+    )?;
+    let mut signature =
+        conn.prepare("INSERT INTO signatures (file_id, position, signature) VALUES (?1, ?2, ?3)")?;
+    let mut import = conn
+        .prepare("INSERT INTO imports (file_id, specifier, resolved_path) VALUES (?1, ?2, ?3)")?;
+
+    let top_level = doc.directories.iter().map(|d| (None, d));
+    let mut package_ids: Vec<(i64, &PackageEntry)> = Vec::new();
+    for entry in &doc.packages {
+        package.execute(params![
+            entry.root,
+            entry.name,
+            entry.version,
+            entry.kind.as_str(),
+            entry.package_path
+        ])?;
+        package_ids.push((conn.last_insert_rowid(), entry));
+    }
+    let in_packages = package_ids
+        .iter()
+        .flat_map(|(id, entry)| entry.directories.iter().map(move |d| (Some(*id), d)));
+
+    for (package_id, dir) in top_level.chain(in_packages) {
+        directory.execute(params![dir.root, dir.directory_path, package_id])?;
+        let directory_id = conn.last_insert_rowid();
+
+        for entry in &dir.files {
+            file.execute(params![
+                directory_id,
+                entry.file_name,
+                entry.relative_file_path,
+                entry.last_scanned,
+                entry.summary,
+                entry.partial,
+                entry.generated,
+                entry.encoding
+            ])?;
+            let file_id = conn.last_insert_rowid();
+
+            for (position, text) in entry.signatures.iter().enumerate() {
+                signature.execute(params![file_id, position as i64, text])?;
+            }
+            for entry in &entry.imports {
+                import.execute(params![file_id, entry.specifier, entry.resolved_path])?;
+            }
+        }
+    }
+
+    let mut dependency =
+        conn.prepare("INSERT INTO dependencies (root, from_path, to_path) VALUES (?1, ?2, ?3)")?;
+    for edge in &doc.dependencies {
+        dependency.execute(params![edge.root, edge.from, edge.to])?;
+    }
+
+    let mut error = conn.prepare(
+        "INSERT INTO errors (root, path, kind, error, line, column) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+    )?;
+    for entry in &doc.errors {
+        error.execute(params![
+            entry.root,
+            entry.path,
+            entry.kind.as_str(),
+            entry.error,
+            entry.line.map(|l| l as i64),
+            entry.column.map(|c| c as i64)
+        ])?;
+    }
+
+    let mut warning = conn.prepare(
+        "INSERT INTO warnings (root, path, line, column, message) VALUES (?1, ?2, ?3, ?4, ?5)",
+    )?;
+    for entry in &doc.warnings {
+        warning.execute(params![
+            entry.root,
+            entry.path,
+            entry.line as i64,
+            entry.column as i64,
+            entry.message
+        ])?;
+    }
+
+    let mut skipped =
+        conn.prepare("INSERT INTO skipped (root, path, reason, detail) VALUES (?1, ?2, ?3, ?4)")?;
+    for entry in &doc.skipped {
+        skipped.execute(params![
+            entry.root,
+            entry.path,
+            entry.reason.as_str(),
+            entry.detail
+        ])?;
+    }
+
+    Ok(())
+}
+
+fn sqlite_error(err: rusqlite::Error) -> TalosError {
+    TalosError::Encode {
+        format: "SQLite",
+        message: err.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::sample_document;
+
+    fn query_i64(conn: &Connection, sql: &str) -> i64 {
+        conn.query_row(sql, [], |row| row.get(0)).unwrap()
+    }
+
+    #[test]
+    fn export_fills_every_table_consistently() {
+        let conn = export(&sample_document()).unwrap();
+        let count = |table: &str| query_i64(&conn, &format!("SELECT COUNT(*) FROM {}", table));
+        assert_eq!(count("packages"), 1);
+        assert_eq!(count("directories"), 2);
+        assert_eq!(count("files"), 3);
+        assert_eq!(count("signatures"), 4);
+        assert_eq!(count("imports"), 2);
+        assert_eq!(count("dependencies"), 1);
+        assert_eq!(count("errors"), 1);
+        assert_eq!(count("warnings"), 1);
+        assert_eq!(count("skipped"), 1);
+
+        // Every reference points at an existing row
+        let violations: i64 = conn
+            .prepare("PRAGMA foreign_key_check")
+            .unwrap()
+            .query_map([], |_| Ok(()))
+            .unwrap()
+            .count() as i64;
+        assert_eq!(violations, 0);
+        assert_eq!(
+            query_i64(
+                &conn,
+                "SELECT COUNT(*) FROM signatures s LEFT JOIN files f ON f.id = s.file_id
+                 WHERE f.id IS NULL"
+            ),
+            0
+        );
+
+        // Files land in their package's directory, with signatures in order
+        assert_eq!(
+            query_i64(
+                &conn,
+                "SELECT COUNT(*) FROM files f JOIN directories d ON d.id = f.directory_id
+                 JOIN packages p ON p.id = d.package_id WHERE p.name = 'core'"
+            ),
+            1
+        );
+        let signatures: Vec<String> = conn
+            .prepare(
+                "SELECT s.signature FROM signatures s JOIN files f ON f.id = s.file_id
+                 WHERE f.path = 'src/widget.tsx' ORDER BY s.position",
+            )
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(
+            signatures,
+            vec!["export class Widget", "method render(props: Props)"]
+        );
+        let unresolved = query_i64(
+            &conn,
+            "SELECT COUNT(*) FROM imports WHERE resolved_path IS NULL",
+        );
+        assert_eq!(unresolved, 1);
+    }
+}
@@ -0,0 +1,370 @@
+use crate::error::TalosResult;
+use crate::extractor::infer_lang_from_ext;
+use crate::model::{DefinitionEntry, Document};
+use crate::writer::DocumentWriter;
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+
+/// Universal-ctags `tags` file with line-number addresses, sorted by name so
+/// editors can binary-search it
+pub struct CtagsWriter;
+
+impl DocumentWriter for CtagsWriter {
+    fn render(&self, doc: &Document) -> TalosResult<Vec<u8>> {
+        let mut tags: Vec<(&str, String, &DefinitionEntry)> = Vec::new();
+        for dir in doc.all_directories() {
+            for file in &dir.files {
+                let path = labelled_path(dir.root.as_deref(), &file.relative_file_path);
+                for def in &file.definitions {
+                    tags.push((def.name.as_str(), path.clone(), def));
+                }
+            }
+        }
+        tags.sort_by(|a, b| (a.0, &a.1, a.2.line).cmp(&(b.0, &b.1, b.2.line)));
+
+        let mut out = String::from(
+            "!_TAG_FILE_FORMAT\t2\t/extended format; --format=1 will not append ;\" to lines/\n\
+             !_TAG_FILE_SORTED\t1\t/0=unsorted, 1=sorted, 2=foldcase/\n\
+             !_TAG_OUTPUT_EXCMD\tnumber\t/number, pattern, mixed, or combineV2/\n\
+             !_TAG_PROGRAM_NAME\ttalos\t//\n",
+        );
+        out.push_str(&format!(
+            "!_TAG_PROGRAM_URL\t{}\t/official site/\n!_TAG_PROGRAM_VERSION\t{}\t//\n",
+            env!("CARGO_PKG_REPOSITORY"),
+            env!("CARGO_PKG_VERSION")
+        ));
+        for (name, path, def) in tags {
+            out.push_str(&format!(
+                "{}\t{}\t{};\"\tkind:{}\tline:{}",
+                name, path, def.line, def.kind, def.line
+            ));
+            if let Some(scope) = &def.scope {
+                out.push('\t');
+                out.push_str(scope);
+            }
+            out.push('\n');
+        }
+        Ok(out.into_bytes())
+    }
+}
+
+/// LSIF dump (one JSON vertex or edge per line) answering go-to-definition
+/// and hover for every definition
+pub struct LsifWriter {
+    /// Absolute directory the summary's paths are relative to
+    pub project_root: PathBuf,
+}
+
+impl DocumentWriter for LsifWriter {
+    fn render(&self, doc: &Document) -> TalosResult<Vec<u8>> {
+        let mut dump = LsifDump::default();
+        dump.vertex(json!({
+            "label": "metaData",
+            "version": "0.6.0",
+            "projectRoot": file_uri(&self.project_root),
+            "positionEncoding": "utf-16",
+            "toolInfo": { "name": "talos", "version": env!("CARGO_PKG_VERSION") },
+        }));
+
+        let mut files = Vec::new();
+        for dir in doc.all_directories() {
+            for file in dir.files.iter().filter(|f| !f.definitions.is_empty()) {
+                let path =
+                    PathBuf::from(labelled_path(dir.root.as_deref(), &file.relative_file_path));
+                let language = infer_lang_from_ext(&path)
+                    .map(|lang| match lang.name() {
+                        "tsx" => "typescriptreact",
+                        name => name,
+                    })
+                    .unwrap_or("plaintext");
+                files.push((path, language, file));
+            }
+        }
+
+        let project = dump.vertex(json!({
+            "label": "project",
+            "kind": project_kind(files.iter().map(|(_, language, _)| *language)),
+            "name": self
+                .project_root
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default(),
+        }));
+        dump.event("begin", "project", project);
+
+        let mut documents = Vec::new();
+        for (path, language, file) in files {
+            let document = dump.vertex(json!({
+                "label": "document",
+                "uri": file_uri(&self.project_root.join(&path)),
+                "languageId": language,
+            }));
+            dump.event("begin", "document", document);
+
+            let ranges: Vec<u64> = file
+                .definitions
+                .iter()
+                .map(|def| dump.definition(document, language, def))
+                .collect();
+            dump.edge(json!({
+                "label": "contains",
+                "outV": document,
+                "inVs": ranges,
+            }));
+            dump.event("end", "document", document);
+            documents.push(document);
+        }
+
+        if !documents.is_empty() {
+            dump.edge(json!({
+                "label": "contains",
+                "outV": project,
+                "inVs": documents,
+            }));
+        }
+        dump.event("end", "project", project);
+
+        let mut out = Vec::new();
+        for element in dump.elements {
+            serde_json::to_writer(&mut out, &element)?;
+            out.push(b'\n');
+        }
+        Ok(out)
+    }
+}
+
+#[derive(Default)]
+struct LsifDump {
+    next_id: u64,
+    elements: Vec<Value>,
+}
+
+impl LsifDump {
+    fn vertex(&mut self, mut vertex: Value) -> u64 {
+        self.push("vertex", &mut vertex)
+    }
+
+    fn edge(&mut self, mut edge: Value) -> u64 {
+        self.push("edge", &mut edge)
+    }
+
+    /// `$event` vertex opening or closing the project or a document
+    fn event(&mut self, kind: &str, scope: &str, data: u64) -> u64 {
+        self.vertex(json!({ "label": "$event", "kind": kind, "scope": scope, "data": data }))
+    }
+
+    fn push(&mut self, kind: &str, element: &mut Value) -> u64 {
+        self.next_id += 1;
+        element["id"] = json!(self.next_id);
+        element["type"] = json!(kind);
+        self.elements.push(element.take());
+        self.next_id
+    }
+
+    /// Range, result set, definition and hover results for one definition;
+    /// returns the range id
+    fn definition(&mut self, document: u64, language: &str, def: &DefinitionEntry) -> u64 {
+        let line = def.line.saturating_sub(1);
+        let start = def.column.saturating_sub(1);
+        let end = start + def.name.encode_utf16().count();
+
+        let result_set = self.vertex(json!({ "label": "resultSet" }));
+        let range = self.vertex(json!({
+            "label": "range",
+            "start": { "line": line, "character": start },
+            "end": { "line": line, "character": end },
+        }));
+        self.edge(json!({ "label": "next", "outV": range, "inV": result_set }));
+
+        let definition = self.vertex(json!({ "label": "definitionResult" }));
+        self.edge(json!({
+            "label": "textDocument/definition",
+            "outV": result_set,
+            "inV": definition,
+        }));
+        self.edge(json!({
+            "label": "item",
+            "outV": definition,
+            "inVs": [range],
+            "document": document,
+        }));
+
+        let hover_text = match &def.scope {
+            Some(scope) => format!("{} {} (in {})", def.kind, def.name, scope),
+            None => format!("{} {}", def.kind, def.name),
+        };
+        let hover = self.vertex(json!({
+            "label": "hoverResult",
+            "result": { "contents": [{ "language": language, "value": hover_text }] },
+        }));
+        self.edge(json!({
+            "label": "textDocument/hover",
+            "outV": result_set,
+            "inV": hover,
+        }));
+        range
+    }
+}
+
+/// The project's language for the `project` vertex: the most common document
+/// language, the first seen winning ties
+fn project_kind<'a>(languages: impl Iterator<Item = &'a str>) -> &'a str {
+    let mut counts: Vec<(&str, usize)> = Vec::new();
+    for language in languages {
+        match counts.iter_mut().find(|(seen, _)| *seen == language) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((language, 1)),
+        }
+    }
+    counts
+        .iter()
+        .rev()
+        .max_by_key(|(_, count)| *count)
+        .map(|(language, _)| *language)
+        .unwrap_or("plaintext")
+}
+
+/// `path` under its root label when several inputs were scanned
+fn labelled_path(root: Option<&str>, path: &str) -> String {
+    match root {
+        Some(root) => Path::new(root).join(path).to_string_lossy().to_string(),
+        None => path.to_string(),
+    }
+}
+
+/// `file://` URI with everything outside the unreserved set percent-encoded
+fn file_uri(path: &Path) -> String {
+    let mut uri = String::from("file://");
+    let path = path.to_string_lossy().replace('\\', "/");
+    if !path.starts_with('/') {
+        uri.push('/');
+    }
+    for byte in path.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' | b':' => {
+                uri.push(byte as char)
+            }
+            _ => uri.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    uri
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::sample_document;
+    use std::collections::HashMap;
+
+    #[test]
+    fn ctags_are_sorted_by_name_with_scopes() {
+        let out = String::from_utf8(CtagsWriter.render(&sample_document()).unwrap()).unwrap();
+        let tags: Vec<&str> = out.lines().filter(|l| !l.starts_with("!_TAG_")).collect();
+        assert_eq!(
+            tags,
+            vec![
+                "Widget\tsrc/widget.tsx\t3;\"\tkind:class\tline:3",
+                "alpha\tsrc/util.ts\t1;\"\tkind:function\tline:1",
+                "render\tsrc/widget.tsx\t4;\"\tkind:method\tline:4\tclass:Widget",
+            ]
+        );
+        assert!(out.contains("!_TAG_FILE_SORTED\t1\t"));
+    }
+
+    #[test]
+    fn lsif_links_ranges_to_their_definitions() {
+        let writer = LsifWriter {
+            project_root: PathBuf::from("/work/app"),
+        };
+        let out = String::from_utf8(writer.render(&sample_document()).unwrap()).unwrap();
+        let elements: Vec<Value> = out
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+
+        // Ids count up from 1, and edges only point at vertices already emitted
+        let mut vertices: HashMap<u64, &Value> = HashMap::new();
+        for (idx, element) in elements.iter().enumerate() {
+            assert_eq!(element["id"], json!(idx as u64 + 1));
+            if element["type"] == "vertex" {
+                vertices.insert(idx as u64 + 1, element);
+                continue;
+            }
+            let targets = element["inVs"]
+                .as_array()
+                .cloned()
+                .unwrap_or_else(|| vec![element["inV"].clone()]);
+            for id in targets.iter().chain([&element["outV"]]) {
+                assert!(vertices.contains_key(&id.as_u64().unwrap()), "{}", element);
+            }
+        }
+        let label = |id: &Value| vertices[&id.as_u64().unwrap()]["label"].clone();
+        let edges = |name: &str| -> Vec<&Value> {
+            elements
+                .iter()
+                .filter(|e| e["type"] == "edge" && e["label"] == name)
+                .collect()
+        };
+
+        // metaData, then the project opened first and closed last
+        assert_eq!(elements[0]["label"], "metaData");
+        assert_eq!(elements[1]["label"], "project");
+        assert_eq!(elements[1]["kind"], "typescript");
+        let event = |e: &Value| (e["label"].clone(), e["kind"].clone(), e["scope"].clone());
+        assert_eq!(
+            event(&elements[2]),
+            (json!("$event"), json!("begin"), json!("project"))
+        );
+        let last = elements.last().unwrap();
+        assert_eq!(
+            event(last),
+            (json!("$event"), json!("end"), json!("project"))
+        );
+
+        let documents: Vec<&Value> = vertices
+            .values()
+            .filter(|v| v["label"] == "document")
+            .copied()
+            .collect();
+        assert_eq!(documents.len(), 2);
+        let project_contains = edges("contains")
+            .into_iter()
+            .find(|e| e["outV"] == elements[1]["id"])
+            .unwrap();
+        assert_eq!(project_contains["inVs"].as_array().unwrap().len(), 2);
+        for document in &documents {
+            let scoped = |kind: &str| {
+                elements.iter().any(|e| {
+                    e["label"] == "$event" && e["kind"] == kind && e["data"] == document["id"]
+                })
+            };
+            assert!(scoped("begin") && scoped("end"));
+        }
+
+        // range -next-> resultSet -textDocument/definition-> definitionResult -item-> range
+        let ranges: Vec<&Value> = vertices
+            .values()
+            .filter(|v| v["label"] == "range")
+            .copied()
+            .collect();
+        assert_eq!(ranges.len(), 3);
+        for range in ranges {
+            let next = edges("next")
+                .into_iter()
+                .find(|e| e["outV"] == range["id"])
+                .unwrap();
+            assert_eq!(label(&next["inV"]), "resultSet");
+            let definition = edges("textDocument/definition")
+                .into_iter()
+                .find(|e| e["outV"] == next["inV"])
+                .unwrap();
+            assert_eq!(label(&definition["inV"]), "definitionResult");
+            let item = edges("item")
+                .into_iter()
+                .find(|e| e["outV"] == definition["inV"])
+                .unwrap();
+            assert_eq!(item["inVs"], json!([range["id"]]));
+            assert_eq!(label(&item["document"]), "document");
+        }
+    }
+}
@@ -18,6 +18,10 @@ impl Default for Extensions {
 }
 
 impl Extensions {
+    pub fn new(extensions: Vec<String>) -> Self {
+        Extensions(extensions)
+    }
+
     pub fn iter(&self) -> impl Iterator<Item = &str> {
         self.0.iter().map(|s| s.as_str())
     }
@@ -60,12 +64,17 @@ pub enum OutputPath {
 
 impl OutputPath {
     pub fn new(input: Option<&str>, default_dir: &Path) -> Self {
+        Self::with_default_name(input, default_dir, "talos.json")
+    }
+
+    /// Like [`OutputPath::new`], with another file name when none is given
+    pub fn with_default_name(input: Option<&str>, default_dir: &Path, name: &str) -> Self {
         match input {
             Some("-") => OutputPath::Stdout,
             Some(path) => OutputPath::File(PathBuf::from(path)),
             None => {
                 let mut default_path = default_dir.to_path_buf();
-                default_path.push("talos.json");
+                default_path.push(name);
                 OutputPath::File(default_path)
             }
         }
@@ -140,3 +149,221 @@ impl From<GlobPatterns> for Vec<String> {
         patterns.0
     }
 }
+
+/// Dependency graph export format
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphFormat {
+    Dot,
+    Mermaid,
+}
+
+impl GraphFormat {
+    /// Pick a format from the output file extension, defaulting to DOT
+    pub fn from_path(path: &str) -> Self {
+        let ext = Path::new(path)
+            .extension()
+            .map(|e| e.to_string_lossy().to_ascii_lowercase())
+            .unwrap_or_default();
+        match ext.as_str() {
+            "mmd" | "mermaid" => GraphFormat::Mermaid,
+            _ => GraphFormat::Dot,
+        }
+    }
+}
+
+impl FromStr for GraphFormat {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "dot" | "graphviz" => Ok(GraphFormat::Dot),
+            "mermaid" | "mmd" => Ok(GraphFormat::Mermaid),
+            _ => Err("Graph format must be 'dot' or 'mermaid'"),
+        }
+    }
+}
+
+/// Summary output format
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    /// Pretty-printed JSON
+    #[default]
+    Json,
+    /// JSON on a single line
+    JsonCompact,
+    Yaml,
+    Toml,
+    /// MessagePack (binary)
+    Msgpack,
+    /// One JSON record per line, written while scanning
+    Ndjson,
+    /// `<file path="...">` blocks for LLM prompts
+    XmlPrompt,
+    /// Self-contained, searchable HTML report
+    Html,
+    /// SQLite database for ad-hoc SQL queries
+    Sqlite,
+    /// Universal-ctags `tags` file
+    Ctags,
+    /// LSIF index of definitions
+    Lsif,
+}
+
+impl OutputFormat {
+    /// Pick a format from the output file extension, defaulting to JSON
+    pub fn from_path(path: &str) -> Self {
+        if Path::new(path)
+            .file_name()
+            .is_some_and(|name| name == "tags")
+        {
+            return OutputFormat::Ctags;
+        }
+        let ext = Path::new(path)
+            .extension()
+            .map(|e| e.to_string_lossy().to_ascii_lowercase())
+            .unwrap_or_default();
+        match ext.as_str() {
+            "yaml" | "yml" => OutputFormat::Yaml,
+            "toml" => OutputFormat::Toml,
+            "msgpack" | "mpk" => OutputFormat::Msgpack,
+            "ndjson" | "jsonl" => OutputFormat::Ndjson,
+            "xml" => OutputFormat::XmlPrompt,
+            "html" | "htm" => OutputFormat::Html,
+            "sqlite" | "sqlite3" | "db" => OutputFormat::Sqlite,
+            "tags" => OutputFormat::Ctags,
+            "lsif" => OutputFormat::Lsif,
+            _ => OutputFormat::Json,
+        }
+    }
+
+    /// Output file name when none is given
+    pub fn default_file_name(&self) -> &'static str {
+        match self {
+            OutputFormat::Json | OutputFormat::JsonCompact => "talos.json",
+            OutputFormat::Yaml => "talos.yaml",
+            OutputFormat::Toml => "talos.toml",
+            OutputFormat::Msgpack => "talos.msgpack",
+            OutputFormat::Ndjson => "talos.ndjson",
+            OutputFormat::XmlPrompt => "talos.xml",
+            OutputFormat::Html => "talos.html",
+            OutputFormat::Sqlite => "talos.sqlite",
+            OutputFormat::Ctags => "tags",
+            OutputFormat::Lsif => "dump.lsif",
+        }
+    }
+
+    /// Symbol index formats, which need each file's definitions
+    pub fn needs_definitions(&self) -> bool {
+        matches!(self, OutputFormat::Ctags | OutputFormat::Lsif)
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "json" => Ok(OutputFormat::Json),
+            "json-compact" => Ok(OutputFormat::JsonCompact),
+            "yaml" | "yml" => Ok(OutputFormat::Yaml),
+            "toml" => Ok(OutputFormat::Toml),
+            "msgpack" | "messagepack" => Ok(OutputFormat::Msgpack),
+            "ndjson" | "jsonl" => Ok(OutputFormat::Ndjson),
+            "xml-prompt" | "xml" => Ok(OutputFormat::XmlPrompt),
+            "html" => Ok(OutputFormat::Html),
+            "sqlite" | "sqlite3" => Ok(OutputFormat::Sqlite),
+            "ctags" | "tags" => Ok(OutputFormat::Ctags),
+            "lsif" => Ok(OutputFormat::Lsif),
+            _ => Err(
+                "Output format must be 'json', 'json-compact', 'yaml', 'toml', 'msgpack', 'ndjson', 'xml-prompt', 'html', 'sqlite', 'ctags' or 'lsif'",
+            ),
+        }
+    }
+}
+
+/// What to do with generated and minified files
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GeneratedFiles {
+    /// Leave them out (listed under `skipped` with `--explain`)
+    #[default]
+    Skip,
+    /// Summarize them, marked `"generated": true`
+    Tag,
+    /// Summarize them like any other file, without detection
+    Keep,
+}
+
+impl FromStr for GeneratedFiles {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "skip" => Ok(GeneratedFiles::Skip),
+            "tag" => Ok(GeneratedFiles::Tag),
+            "keep" => Ok(GeneratedFiles::Keep),
+            _ => Err("Generated file handling must be 'skip', 'tag' or 'keep'"),
+        }
+    }
+}
+
+/// `--plugin` specification: `<ext>[,<ext>...]=<command>`
+#[derive(Debug, Clone)]
+pub struct PluginSpec {
+    pub extensions: Vec<String>,
+    pub command: String,
+}
+
+impl PluginSpec {
+    /// Plugin name for messages: its first extension
+    pub fn name(&self) -> &str {
+        self.extensions
+            .first()
+            .map(String::as_str)
+            .unwrap_or_default()
+    }
+}
+
+impl FromStr for PluginSpec {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (exts, command) = s
+            .split_once('=')
+            .ok_or("Plugin must be given as <ext>[,<ext>...]=<command>")?;
+        let extensions = Extensions::from_str(exts)?.into();
+        let command = command.trim();
+        if command.is_empty() {
+            return Err("Plugin command is empty");
+        }
+        Ok(PluginSpec {
+            extensions,
+            command: command.to_string(),
+        })
+    }
+}
+
+/// `--lang-map` entry: `<ext>=<ext or language>`, e.g. `mjs=js` or `es6=javascript`
+#[derive(Debug, Clone)]
+pub struct LangMapping {
+    pub extension: String,
+    pub target: String,
+}
+
+impl FromStr for LangMapping {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (extension, target) = s
+            .split_once('=')
+            .ok_or("Language mapping must be given as <ext>=<ext or language>")?;
+        let extension = extension.trim().trim_start_matches('.');
+        let target = target.trim();
+        if extension.is_empty() || target.is_empty() {
+            return Err("Language mapping needs both an extension and a target");
+        }
+        Ok(LangMapping {
+            extension: extension.to_ascii_lowercase(),
+            target: target.to_string(),
+        })
+    }
+}
@@ -0,0 +1,574 @@
+use crate::error::{TalosError, TalosResult};
+use crate::model::{ErrorEntry, PackageKind};
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use ignore::WalkBuilder;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+/// Directories never searched for manifests
+const SKIP_DIRS: &[&str] = &["node_modules", ".git", "target"];
+
+/// package.json fields naming entry points, in the order they are listed
+const NPM_ENTRY_FIELDS: &[&str] = &["source", "main", "module", "browser", "types", "typings"];
+
+/// A workspace member: an npm/yarn/pnpm workspace package or a Cargo crate
+#[derive(Debug, Clone)]
+pub struct Package {
+    pub name: String,
+    pub version: Option<String>,
+    pub kind: PackageKind,
+    /// Package directory relative to the scan root (empty for the root itself)
+    pub dir: PathBuf,
+    /// Entry points relative to the scan root
+    pub entry_points: Vec<String>,
+}
+
+/// Member patterns declared by a workspace manifest
+struct Workspace {
+    dir: PathBuf,
+    kind: PackageKind,
+    members: Vec<String>,
+    excludes: Vec<String>,
+    /// `[workspace.package] version`, inherited with `version.workspace = true`
+    version: Option<String>,
+}
+
+/// Find workspace roots at or above `root` and inside it, and load their
+/// members that live under `root`. Malformed `Cargo.toml` and
+/// `pnpm-workspace.yaml` files are reported; broken `package.json` files are
+/// left to the import resolver, which reports them already.
+pub fn discover_packages(root: &Path, files: &ProjectFiles) -> (Vec<Package>, Vec<ErrorEntry>) {
+    let mut errors = Vec::new();
+    let manifests = &files.by_dir;
+
+    let mut workspaces = Vec::new();
+    for (dir, names) in manifests {
+        for name in names {
+            if !is_manifest(name) {
+                continue;
+            }
+            match load_workspace(dir, name) {
+                Ok(Some(workspace)) => workspaces.push(workspace),
+                Ok(None) => {}
+                Err(e) => errors.push(ErrorEntry::from_error(
+                    display_path(&dir.join(name), root),
+                    &e,
+                )),
+            }
+        }
+    }
+
+    let mut packages: BTreeMap<PathBuf, Package> = BTreeMap::new();
+    for workspace in &workspaces {
+        let matcher = match MemberMatcher::new(workspace) {
+            Ok(matcher) => matcher,
+            Err(e) => {
+                let manifest = workspace.dir.join(manifest_name(workspace.kind));
+                errors.push(ErrorEntry::from_error(display_path(&manifest, root), &e));
+                continue;
+            }
+        };
+        for (dir, names) in manifests.range(root.to_path_buf()..) {
+            if !dir.starts_with(root) {
+                break;
+            }
+            let manifest = manifest_name(workspace.kind);
+            if !names.iter().any(|n| n == manifest) || !matcher.is_member(dir) {
+                continue;
+            }
+            let rel_dir = dir.strip_prefix(root).unwrap_or(dir).to_path_buf();
+            if packages.contains_key(&rel_dir) {
+                continue;
+            }
+            let loaded = match workspace.kind {
+                PackageKind::Npm => Ok(load_npm_package(dir, &rel_dir)),
+                PackageKind::Cargo => load_cargo_package(dir, &rel_dir, workspace),
+            };
+            match loaded {
+                Ok(Some(package)) => {
+                    packages.insert(rel_dir, package);
+                }
+                Ok(None) => {}
+                Err(e) => errors.push(ErrorEntry::from_error(
+                    display_path(&dir.join(manifest), root),
+                    &e,
+                )),
+            }
+        }
+    }
+
+    (packages.into_values().collect(), errors)
+}
+
+/// Manifest and tsconfig/jsconfig file names by directory, gathered in one walk
+/// for both workspace discovery and the import resolver: everything under the
+/// root, plus manifests in the directories above it up to the enclosing repository
+#[derive(Debug, Default)]
+pub struct ProjectFiles {
+    by_dir: BTreeMap<PathBuf, Vec<String>>,
+}
+
+impl ProjectFiles {
+    pub fn find(root: &Path) -> Self {
+        let mut by_dir: BTreeMap<PathBuf, Vec<String>> = BTreeMap::new();
+
+        let mut walker = WalkBuilder::new(root);
+        walker
+            .hidden(false)
+            .git_ignore(true)
+            .parents(true)
+            .filter_entry(|e| !SKIP_DIRS.iter().any(|skip| e.file_name() == *skip));
+        for dent in walker.build().flatten() {
+            let name = dent.file_name().to_string_lossy();
+            if !(is_manifest(&name) || is_tsconfig(&name))
+                || !dent.file_type().is_some_and(|ft| ft.is_file())
+            {
+                continue;
+            }
+            if let Some(dir) = dent.path().parent() {
+                by_dir
+                    .entry(dir.to_path_buf())
+                    .or_default()
+                    .push(name.to_string());
+            }
+        }
+
+        for dir in root.ancestors().skip(1) {
+            let Ok(entries) = fs::read_dir(dir) else {
+                break;
+            };
+            for entry in entries.flatten() {
+                let name = entry.file_name().to_string_lossy().to_string();
+                if is_manifest(&name) {
+                    by_dir.entry(dir.to_path_buf()).or_default().push(name);
+                }
+            }
+            if dir.join(".git").exists() {
+                break;
+            }
+        }
+
+        for names in by_dir.values_mut() {
+            names.sort();
+        }
+        ProjectFiles { by_dir }
+    }
+
+    /// `package.json`, `tsconfig.json` and `jsconfig.json` files under `root`
+    pub fn js_configs<'a>(&'a self, root: &'a Path) -> impl Iterator<Item = PathBuf> + 'a {
+        self.by_dir
+            .range(root.to_path_buf()..)
+            .take_while(move |(dir, _)| dir.starts_with(root))
+            .flat_map(|(dir, names)| {
+                names
+                    .iter()
+                    .filter(|name| *name == "package.json" || is_tsconfig(name))
+                    .map(move |name| dir.join(name))
+            })
+    }
+}
+
+fn is_manifest(name: &str) -> bool {
+    matches!(
+        name,
+        "package.json" | "pnpm-workspace.yaml" | "pnpm-workspace.yml" | "Cargo.toml"
+    )
+}
+
+fn is_tsconfig(name: &str) -> bool {
+    matches!(name, "tsconfig.json" | "jsconfig.json")
+}
+
+fn manifest_name(kind: PackageKind) -> &'static str {
+    match kind {
+        PackageKind::Npm => "package.json",
+        PackageKind::Cargo => "Cargo.toml",
+    }
+}
+
+/// Workspace declared by `dir/name`, if it declares one
+fn load_workspace(dir: &Path, name: &str) -> TalosResult<Option<Workspace>> {
+    let path = dir.join(name);
+    let workspace = |kind, patterns: Vec<String>, excludes: Vec<String>, version| {
+        // npm-style `!pattern` entries exclude
+        let (negated, members): (Vec<String>, Vec<String>) =
+            patterns.into_iter().partition(|p| p.starts_with('!'));
+        let mut excludes = excludes;
+        excludes.extend(negated.iter().map(|p| p[1..].to_string()));
+        Some(Workspace {
+            dir: dir.to_path_buf(),
+            kind,
+            members,
+            excludes,
+            version,
+        })
+    };
+
+    match name {
+        "package.json" => {
+            let Some(json) = read_json(&path) else {
+                return Ok(None);
+            };
+            // `"workspaces": [...]` or yarn's `"workspaces": { "packages": [...] }`
+            let patterns = json
+                .get("workspaces")
+                .map(|w| w.get("packages").unwrap_or(w))
+                .map(string_list);
+            Ok(patterns.and_then(|p| workspace(PackageKind::Npm, p, Vec::new(), None)))
+        }
+        "pnpm-workspace.yaml" | "pnpm-workspace.yml" => {
+            let yaml: serde_yaml::Value = serde_yaml::from_str(&fs::read_to_string(&path)?)?;
+            let patterns = yaml
+                .get("packages")
+                .and_then(|p| p.as_sequence())
+                .map(|seq| {
+                    seq.iter()
+                        .filter_map(|v| v.as_str().map(String::from))
+                        .collect()
+                })
+                .unwrap_or_default();
+            Ok(workspace(PackageKind::Npm, patterns, Vec::new(), None))
+        }
+        "Cargo.toml" => {
+            let manifest = read_toml(&path)?;
+            let Some(ws) = manifest.get("workspace").and_then(|w| w.as_table()) else {
+                return Ok(None);
+            };
+            let list = |key: &str| -> Vec<String> {
+                ws.get(key)
+                    .and_then(|v| v.as_array())
+                    .map(|items| {
+                        items
+                            .iter()
+                            .filter_map(|v| v.as_str().map(String::from))
+                            .collect()
+                    })
+                    .unwrap_or_default()
+            };
+            let mut members = list("members");
+            // A root `[package]` is a member too
+            if manifest.contains_key("package") {
+                members.push(".".to_string());
+            }
+            let version = ws
+                .get("package")
+                .and_then(|p| p.get("version"))
+                .and_then(|v| v.as_str())
+                .map(String::from);
+            Ok(workspace(
+                PackageKind::Cargo,
+                members,
+                list("exclude"),
+                version,
+            ))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Matches member directories against a workspace's patterns
+struct MemberMatcher<'a> {
+    workspace: &'a Workspace,
+    members: GlobSet,
+    excludes: GlobSet,
+}
+
+impl<'a> MemberMatcher<'a> {
+    fn new(workspace: &'a Workspace) -> TalosResult<Self> {
+        Ok(Self {
+            workspace,
+            members: member_globset(&workspace.members)?,
+            excludes: member_globset(&workspace.excludes)?,
+        })
+    }
+
+    fn is_member(&self, dir: &Path) -> bool {
+        let Ok(rel) = dir.strip_prefix(&self.workspace.dir) else {
+            return false;
+        };
+        let rel = if rel.as_os_str().is_empty() {
+            Path::new(".")
+        } else {
+            rel
+        };
+        // Cargo excludes cover everything below the excluded path
+        let excluded = rel
+            .ancestors()
+            .filter(|p| !p.as_os_str().is_empty())
+            .any(|p| self.excludes.is_match(p));
+        self.members.is_match(rel) && !excluded
+    }
+}
+
+fn member_globset(patterns: &[String]) -> TalosResult<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let pattern = pattern
+            .trim()
+            .trim_start_matches("./")
+            .trim_end_matches('/');
+        let pattern = if pattern.is_empty() { "." } else { pattern };
+        let glob = GlobBuilder::new(pattern)
+            .literal_separator(true)
+            .build()
+            .map_err(|e| {
+                TalosError::InvalidInput(format!("Invalid workspace pattern '{}': {}", pattern, e))
+            })?;
+        builder.add(glob);
+    }
+    builder
+        .build()
+        .map_err(|e| TalosError::InvalidInput(format!("Failed to build workspace globset: {}", e)))
+}
+
+fn load_npm_package(dir: &Path, rel_dir: &Path) -> Option<Package> {
+    let json = read_json(&dir.join("package.json"))?;
+    let name = json.get("name")?.as_str()?.to_string();
+
+    let mut entries: Vec<&str> = NPM_ENTRY_FIELDS
+        .iter()
+        .filter_map(|field| json.get(*field).and_then(Value::as_str))
+        .collect();
+    // `"bin": "cli.js"` or `"bin": { "tool": "cli.js" }`
+    match json.get("bin") {
+        Some(Value::String(bin)) => entries.push(bin),
+        Some(Value::Object(bins)) => entries.extend(bins.values().filter_map(Value::as_str)),
+        _ => {}
+    }
+
+    Some(Package {
+        name,
+        version: json
+            .get("version")
+            .and_then(Value::as_str)
+            .map(String::from),
+        kind: PackageKind::Npm,
+        dir: rel_dir.to_path_buf(),
+        entry_points: entry_points(rel_dir, entries),
+    })
+}
+
+fn load_cargo_package(
+    dir: &Path,
+    rel_dir: &Path,
+    workspace: &Workspace,
+) -> TalosResult<Option<Package>> {
+    let manifest = read_toml(&dir.join("Cargo.toml"))?;
+    let Some(package) = manifest.get("package").and_then(|p| p.as_table()) else {
+        return Ok(None);
+    };
+    let Some(name) = package.get("name").and_then(|n| n.as_str()) else {
+        return Ok(None);
+    };
+
+    // `version = "1.2.0"` or `version.workspace = true`
+    let version = match package.get("version") {
+        Some(toml::Value::String(version)) => Some(version.clone()),
+        Some(toml::Value::Table(_)) => workspace.version.clone(),
+        _ => None,
+    };
+
+    let target_path = |table: Option<&toml::Value>| {
+        table
+            .and_then(|t| t.get("path"))
+            .and_then(|p| p.as_str())
+            .map(String::from)
+    };
+    let mut entries: Vec<String> = Vec::new();
+    match target_path(manifest.get("lib")) {
+        Some(lib) => entries.push(lib),
+        None if dir.join("src/lib.rs").is_file() => entries.push("src/lib.rs".to_string()),
+        None => {}
+    }
+    let bins = manifest.get("bin").and_then(|b| b.as_array());
+    let bin_paths: Vec<String> = bins
+        .into_iter()
+        .flatten()
+        .filter_map(|bin| target_path(Some(bin)))
+        .collect();
+    if bin_paths.is_empty() && dir.join("src/main.rs").is_file() {
+        entries.push("src/main.rs".to_string());
+    }
+    entries.extend(bin_paths);
+
+    Ok(Some(Package {
+        name: name.to_string(),
+        version,
+        kind: PackageKind::Cargo,
+        dir: rel_dir.to_path_buf(),
+        entry_points: entry_points(rel_dir, entries.iter().map(String::as_str).collect()),
+    }))
+}
+
+/// Package-relative entry points made relative to the scan root, deduplicated
+fn entry_points(rel_dir: &Path, entries: Vec<&str>) -> Vec<String> {
+    let mut out: Vec<String> = Vec::new();
+    for entry in entries {
+        let path: PathBuf = rel_dir
+            .join(entry)
+            .components()
+            .filter(|c| !matches!(c, Component::CurDir))
+            .collect();
+        let path = path.to_string_lossy().to_string();
+        if !out.contains(&path) {
+            out.push(path);
+        }
+    }
+    out
+}
+
+fn string_list(value: &Value) -> Vec<String> {
+    value
+        .as_array()
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(Value::as_str)
+                .map(String::from)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn read_json(path: &Path) -> Option<Value> {
+    serde_json::from_str(&fs::read_to_string(path).ok()?).ok()
+}
+
+fn read_toml(path: &Path) -> TalosResult<toml::Table> {
+    let raw = fs::read_to_string(path)?;
+    raw.parse::<toml::Table>().map_err(|e| TalosError::Config {
+        message: e.message().to_string(),
+        position: e.span().map(|span| line_column(&raw, span.start)),
+    })
+}
+
+/// 1-based line and column of a byte offset
+fn line_column(text: &str, offset: usize) -> (usize, usize) {
+    let before = text.get(..offset).unwrap_or(text);
+    let line_start = before.rfind('\n').map_or(0, |idx| idx + 1);
+    (
+        before.matches('\n').count() + 1,
+        before[line_start..].chars().count() + 1,
+    )
+}
+
+/// `path` relative to `root` when inside it, as-is otherwise
+fn display_path(path: &Path, root: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(root: &Path, rel: &str, contents: &str) {
+        let path = root.join(rel);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+
+    /// Packages found under `root`, treated as a repository root so the
+    /// directories above it are not searched
+    fn discover(root: &Path) -> Vec<Package> {
+        fs::create_dir_all(root.join(".git")).unwrap();
+        let (packages, errors) = discover_packages(root, &ProjectFiles::find(root));
+        assert!(errors.is_empty(), "{:?}", errors);
+        packages
+    }
+
+    #[test]
+    fn npm_workspaces_with_negated_patterns() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        write(
+            root,
+            "package.json",
+            r#"{ "workspaces": ["packages/*", "!packages/old"] }"#,
+        );
+        write(
+            root,
+            "packages/ui/package.json",
+            r#"{ "name": "@acme/ui", "main": "./dist/index.js", "bin": { "ui": "cli.js" } }"#,
+        );
+        write(root, "packages/old/package.json", r#"{ "name": "old" }"#);
+
+        let packages = discover(root);
+        let found: Vec<(&str, &Path, &[String])> = packages
+            .iter()
+            .map(|p| (p.name.as_str(), p.dir.as_path(), p.entry_points.as_slice()))
+            .collect();
+        let entries = [
+            "packages/ui/dist/index.js".to_string(),
+            "packages/ui/cli.js".to_string(),
+        ];
+        assert_eq!(
+            found,
+            vec![("@acme/ui", Path::new("packages/ui"), &entries[..])]
+        );
+    }
+
+    #[test]
+    fn pnpm_workspace_members() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        write(root, "pnpm-workspace.yaml", "packages:\n  - 'apps/**'\n");
+        write(root, "apps/web/site/package.json", r#"{ "name": "site" }"#);
+        write(root, "tools/package.json", r#"{ "name": "tools" }"#);
+
+        let names: Vec<String> = discover(root).into_iter().map(|p| p.name).collect();
+        assert_eq!(names, vec!["site"]);
+    }
+
+    #[test]
+    fn cargo_workspace_members_and_root_package() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        write(
+            root,
+            "Cargo.toml",
+            "[workspace]\nmembers = [\"crates/*\"]\nexclude = [\"crates/skip\"]\n\
+             [workspace.package]\nversion = \"1.2.0\"\n\
+             [package]\nname = \"app\"\nversion = \"0.1.0\"\n",
+        );
+        write(root, "src/main.rs", "fn main() {}\n");
+        write(
+            root,
+            "crates/core/Cargo.toml",
+            "[package]\nname = \"core\"\nversion.workspace = true\n",
+        );
+        write(root, "crates/core/src/lib.rs", "");
+        write(
+            root,
+            "crates/skip/Cargo.toml",
+            "[package]\nname = \"skip\"\n",
+        );
+
+        let packages = discover(root);
+        let found: Vec<(&str, Option<&str>, &[String])> = packages
+            .iter()
+            .map(|p| {
+                (
+                    p.name.as_str(),
+                    p.version.as_deref(),
+                    p.entry_points.as_slice(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("app", Some("0.1.0"), &["src/main.rs".to_string()][..]),
+                (
+                    "core",
+                    Some("1.2.0"),
+                    &["crates/core/src/lib.rs".to_string()][..]
+                ),
+            ]
+        );
+    }
+}
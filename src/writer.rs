@@ -1,33 +1,495 @@
 use crate::error::{TalosError, TalosResult};
-use crate::model::Document;
-use crate::types::OutputPath;
+use crate::html::HtmlWriter;
+use crate::model::{
+    DependencyEdge, DirectoryEntry, Document, ErrorEntry, FileEntry, FileLocation, PackageEntry,
+    SkipReason, SkippedEntry, WarningEntry, SCHEMA_VERSION,
+};
+use crate::scanner::ScanSink;
+use crate::sqlite::SqliteWriter;
+use crate::symbols::{CtagsWriter, LsifWriter};
+use crate::types::{OutputFormat, OutputPath};
+use serde::Serialize;
+use std::collections::BTreeSet;
 use std::fs::{self, File};
-use std::io::Write;
+use std::io::{self, BufWriter, Write};
 use std::path::{Path, PathBuf};
 use std::time::{SystemTime, UNIX_EPOCH};
 
-pub fn write_output(doc: &Document, output: &OutputPath) -> TalosResult<()> {
-    let json = serde_json::to_string_pretty(doc)?;
+/// Serialises a whole [`Document`] in one output format
+pub trait DocumentWriter {
+    fn render(&self, doc: &Document) -> TalosResult<Vec<u8>>;
+}
+
+/// JSON, pretty-printed or on one line
+pub struct JsonWriter {
+    pub pretty: bool,
+}
+
+impl DocumentWriter for JsonWriter {
+    fn render(&self, doc: &Document) -> TalosResult<Vec<u8>> {
+        let mut out = if self.pretty {
+            serde_json::to_vec_pretty(doc)?
+        } else {
+            serde_json::to_vec(doc)?
+        };
+        out.push(b'\n');
+        Ok(out)
+    }
+}
+
+pub struct YamlWriter;
+
+impl DocumentWriter for YamlWriter {
+    fn render(&self, doc: &Document) -> TalosResult<Vec<u8>> {
+        serde_yaml::to_string(doc)
+            .map(String::into_bytes)
+            .map_err(|e| encode_error("YAML", e))
+    }
+}
+
+pub struct TomlWriter;
+
+impl DocumentWriter for TomlWriter {
+    fn render(&self, doc: &Document) -> TalosResult<Vec<u8>> {
+        toml::to_string(doc)
+            .map(String::into_bytes)
+            .map_err(|e| encode_error("TOML", e))
+    }
+}
+
+/// MessagePack, with struct fields as map keys so readers don't depend on field order
+pub struct MsgpackWriter;
+
+impl DocumentWriter for MsgpackWriter {
+    fn render(&self, doc: &Document) -> TalosResult<Vec<u8>> {
+        rmp_serde::to_vec_named(doc).map_err(|e| encode_error("MessagePack", e))
+    }
+}
+
+/// Files as `<file path="...">` blocks for LLM prompts, optionally after a
+/// `<project_layout>` preamble listing packages and directories. The whole
+/// output is one `<project>` element, so it also parses as an XML document.
+pub struct XmlPromptWriter {
+    pub preamble: bool,
+}
+
+impl DocumentWriter for XmlPromptWriter {
+    fn render(&self, doc: &Document) -> TalosResult<Vec<u8>> {
+        let mut out = String::from("<project>\n");
+        if self.preamble {
+            write_layout(&mut out, doc);
+        }
+        out.push_str("<files>\n");
+        for dir in doc.all_directories() {
+            for file in &dir.files {
+                write_file_block(&mut out, dir.root.as_deref(), file);
+            }
+        }
+        out.push_str("</files>\n</project>\n");
+        Ok(out.into_bytes())
+    }
+}
+
+fn write_layout(out: &mut String, doc: &Document) {
+    let dirs: Vec<&DirectoryEntry> = doc.all_directories().collect();
+    let files: usize = dirs.iter().map(|d| d.files.len()).sum();
+
+    out.push_str("<project_layout>\n");
+    out.push_str(&format!(
+        "Signatures of {} in {}, scanned {}. Each file block lists one source file's \
+         declarations without their bodies.\n",
+        count(files, "file"),
+        count(dirs.len(), "directory"),
+        xml_escape(&doc.last_updated)
+    ));
+    if !doc.packages.is_empty() {
+        out.push_str("\nPackages:\n");
+        for package in &doc.packages {
+            let version = package
+                .version
+                .as_deref()
+                .map(|v| format!(" {}", v))
+                .unwrap_or_default();
+            out.push_str(&format!(
+                "- {}{} ({}{}): {}\n",
+                root_prefix(package.root.as_deref()),
+                xml_escape(&package.name),
+                package.kind.as_str(),
+                xml_escape(&version),
+                xml_escape(&package.package_path)
+            ));
+        }
+    }
+    out.push_str("\nDirectories:\n");
+    for dir in dirs {
+        out.push_str(&format!(
+            "- {}{} ({})\n",
+            root_prefix(dir.root.as_deref()),
+            xml_escape(&dir.directory_path),
+            count(dir.files.len(), "file")
+        ));
+    }
+    out.push_str("</project_layout>\n");
+}
+
+fn write_file_block(out: &mut String, root: Option<&str>, file: &FileEntry) {
+    out.push_str(&format!(
+        "<file path=\"{}\"",
+        xml_attr(&file.relative_file_path)
+    ));
+    if let Some(root) = root {
+        out.push_str(&format!(" root=\"{}\"", xml_attr(root)));
+    }
+    if file.generated {
+        out.push_str(" generated=\"true\"");
+    }
+    if file.partial {
+        out.push_str(" partial=\"true\"");
+    }
+    out.push_str(">\n");
+
+    if let Some(summary) = &file.summary {
+        out.push_str(&format!("<summary>{}</summary>\n", xml_escape(summary)));
+    }
+    write_section(
+        out,
+        "signatures",
+        file.signatures.iter().map(|s| xml_escape(s)),
+    );
+    write_section(
+        out,
+        "components",
+        file.components.iter().map(|c| {
+            let mut line = format!("{} ({})", c.name, c.kind);
+            if let Some(props) = &c.props {
+                line.push_str(&format!(", props {}", props));
+            }
+            if !c.hooks.is_empty() {
+                line.push_str(&format!(", hooks {}", c.hooks.join(", ")));
+            }
+            xml_escape(&line)
+        }),
+    );
+    write_section(
+        out,
+        "imports",
+        file.imports.iter().map(|i| match &i.resolved_path {
+            Some(target) => xml_escape(&format!("{} -> {}", i.specifier, target)),
+            None => xml_escape(&i.specifier),
+        }),
+    );
+    out.push_str("</file>\n");
+}
+
+/// `<tag>` with one line per item, left out when there are none
+fn write_section(out: &mut String, tag: &str, lines: impl Iterator<Item = String>) {
+    let mut lines = lines.peekable();
+    if lines.peek().is_none() {
+        return;
+    }
+    out.push_str(&format!("<{}>\n", tag));
+    for line in lines {
+        out.push_str(&line);
+        out.push('\n');
+    }
+    out.push_str(&format!("</{}>\n", tag));
+}
+
+fn count(n: usize, noun: &str) -> String {
+    match (n, noun) {
+        (1, _) => format!("1 {}", noun),
+        (_, "directory") => format!("{} directories", n),
+        _ => format!("{} {}s", n, noun),
+    }
+}
+
+fn root_prefix(root: Option<&str>) -> String {
+    root.map(|r| format!("{}: ", xml_escape(r)))
+        .unwrap_or_default()
+}
 
+/// Escape a double-quoted attribute value
+/// Escape an attribute value; whitespace other than spaces becomes character
+/// references, since parsers turn raw tabs and line breaks into spaces
+fn xml_attr(text: &str) -> String {
+    xml_escape(text)
+        .replace('"', "&quot;")
+        .replace('\t', "&#9;")
+        .replace('\n', "&#10;")
+        .replace('\r', "&#13;")
+}
+
+/// Escape XML text content. Quotes are left alone so signatures stay readable.
+fn xml_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            // Not allowed anywhere in XML 1.0, even as character references
+            '\u{0}'..='\u{8}'
+            | '\u{B}'
+            | '\u{C}'
+            | '\u{E}'..='\u{1F}'
+            | '\u{FFFE}'
+            | '\u{FFFF}' => escaped.push('\u{FFFD}'),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Settings for writers that have any
+#[derive(Debug, Clone, Default)]
+pub struct WriterOptions {
+    /// Start `xml-prompt` output with a project layout
+    pub preamble: bool,
+    /// Absolute directory the document's paths are relative to, for LSIF URIs
+    pub project_root: PathBuf,
+}
+
+/// The writer for a whole-document format. NDJSON has none: it is written
+/// while scanning by [`NdjsonWriter`].
+pub fn document_writer(
+    format: OutputFormat,
+    options: &WriterOptions,
+) -> Option<Box<dyn DocumentWriter>> {
+    match format {
+        OutputFormat::Json => Some(Box::new(JsonWriter { pretty: true })),
+        OutputFormat::JsonCompact => Some(Box::new(JsonWriter { pretty: false })),
+        OutputFormat::Yaml => Some(Box::new(YamlWriter)),
+        OutputFormat::Toml => Some(Box::new(TomlWriter)),
+        OutputFormat::Msgpack => Some(Box::new(MsgpackWriter)),
+        OutputFormat::XmlPrompt => Some(Box::new(XmlPromptWriter {
+            preamble: options.preamble,
+        })),
+        OutputFormat::Html => Some(Box::new(HtmlWriter)),
+        OutputFormat::Sqlite => Some(Box::new(SqliteWriter)),
+        OutputFormat::Ctags => Some(Box::new(CtagsWriter)),
+        OutputFormat::Lsif => Some(Box::new(LsifWriter {
+            project_root: options.project_root.clone(),
+        })),
+        OutputFormat::Ndjson => None,
+    }
+}
+
+fn encode_error(format: &'static str, err: impl std::fmt::Display) -> TalosError {
+    TalosError::Encode {
+        format,
+        message: err.to_string(),
+    }
+}
+
+pub fn write_output(
+    doc: &Document,
+    output: &OutputPath,
+    writer: &dyn DocumentWriter,
+) -> TalosResult<()> {
+    let content = writer.render(doc)?;
     match output {
         OutputPath::Stdout => {
-            println!("{}", json);
+            let mut stdout = io::stdout().lock();
+            stdout.write_all(&content)?;
+            stdout.flush()?;
             Ok(())
         }
-        OutputPath::File(path) => write_to_file(&json, path),
+        OutputPath::File(path) => write_to_file(&content, path),
     }
 }
 
-fn write_to_file(json: &str, output_path: &Path) -> TalosResult<()> {
+/// Write already-rendered content (DOT, Mermaid, ...) to stdout or atomically to a file
+pub fn write_text(content: &str, output: &OutputPath) -> TalosResult<()> {
+    match output {
+        OutputPath::Stdout => {
+            println!("{}", content);
+            Ok(())
+        }
+        OutputPath::File(path) => write_to_file(content.as_bytes(), path),
+    }
+}
+
+fn write_to_file(content: &[u8], output_path: &Path) -> TalosResult<()> {
     ensure_parent_directory(output_path)?;
 
     let temp_file = TempFile::new(output_path)?;
-    temp_file.write_content(json)?;
+    temp_file.write_content(content)?;
     temp_file.commit(output_path)?;
 
     Ok(())
 }
 
+/// One NDJSON line. The `type` field tells records apart.
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+enum Record<'a> {
+    Header {
+        schema_version: &'a str,
+        last_updated: &'a str,
+    },
+    Package(&'a PackageEntry),
+    File {
+        #[serde(flatten)]
+        location: &'a FileLocation,
+        #[serde(flatten)]
+        file: &'a FileEntry,
+    },
+    Dependency(&'a DependencyEdge),
+    Error(&'a ErrorEntry),
+    Warning(&'a WarningEntry),
+    Skipped(&'a SkippedEntry),
+    /// Always the last line, so readers can tell a complete stream from a cut-off one
+    Summary {
+        files: usize,
+        errors: usize,
+        warnings: usize,
+        skipped: usize,
+    },
+}
+
+/// Writes scan results as newline-delimited JSON while the scan runs, one
+/// record per line, so memory stays flat however large the project is.
+/// File output goes to a temporary file that only replaces the target on
+/// [`NdjsonWriter::finish`].
+pub struct NdjsonWriter {
+    output: NdjsonOutput,
+    started: bool,
+    files: usize,
+    errors: usize,
+    warnings: usize,
+    skipped: usize,
+    warning_files: BTreeSet<String>,
+    generated: usize,
+}
+
+enum NdjsonOutput {
+    Stdout(BufWriter<io::StdoutLock<'static>>),
+    File {
+        // Declared first so it is flushed and closed before the temp file is removed
+        out: BufWriter<File>,
+        temp: TempFile,
+        target: PathBuf,
+    },
+}
+
+impl NdjsonWriter {
+    pub fn create(output: &OutputPath) -> TalosResult<Self> {
+        let output = match output {
+            OutputPath::Stdout => NdjsonOutput::Stdout(BufWriter::new(io::stdout().lock())),
+            OutputPath::File(path) => {
+                ensure_parent_directory(path)?;
+                let temp = TempFile::new(path)?;
+                let file = File::create(&temp.path)?;
+                NdjsonOutput::File {
+                    out: BufWriter::new(file),
+                    temp,
+                    target: path.clone(),
+                }
+            }
+        };
+        Ok(Self {
+            output,
+            started: false,
+            files: 0,
+            errors: 0,
+            warnings: 0,
+            skipped: 0,
+            warning_files: BTreeSet::new(),
+            generated: 0,
+        })
+    }
+
+    /// Number of files with syntax or decoding warnings so far
+    pub fn warning_count(&self) -> usize {
+        self.warning_files.len()
+    }
+
+    /// Number of files skipped as generated so far
+    pub fn generated_count(&self) -> usize {
+        self.generated
+    }
+
+    /// Write the summary line, flush everything and move file output into place.
+    /// The temp file is synced and closed first: Windows can't rename an open file.
+    pub fn finish(mut self) -> TalosResult<()> {
+        self.record(&Record::Summary {
+            files: self.files,
+            errors: self.errors,
+            warnings: self.warnings,
+            skipped: self.skipped,
+        })?;
+        match self.output {
+            NdjsonOutput::Stdout(mut out) => out.flush()?,
+            NdjsonOutput::File { out, temp, target } => {
+                let file = out.into_inner().map_err(|e| e.into_error())?;
+                file.sync_all()?;
+                drop(file);
+                temp.commit(&target)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn record(&mut self, record: &Record) -> TalosResult<()> {
+        let out: &mut dyn Write = match &mut self.output {
+            NdjsonOutput::Stdout(out) => out,
+            NdjsonOutput::File { out, .. } => out,
+        };
+        serde_json::to_writer(&mut *out, record)?;
+        out.write_all(b"\n")?;
+        Ok(())
+    }
+}
+
+impl ScanSink for NdjsonWriter {
+    fn start(&mut self, last_updated: &str) -> TalosResult<()> {
+        // Several roots share one header
+        if std::mem::replace(&mut self.started, true) {
+            return Ok(());
+        }
+        self.record(&Record::Header {
+            schema_version: SCHEMA_VERSION,
+            last_updated,
+        })
+    }
+
+    fn package(&mut self, package: PackageEntry) -> TalosResult<()> {
+        self.record(&Record::Package(&package))
+    }
+
+    fn file(&mut self, location: &FileLocation, file: FileEntry) -> TalosResult<()> {
+        self.files += 1;
+        self.record(&Record::File {
+            location,
+            file: &file,
+        })
+    }
+
+    fn dependency(&mut self, edge: DependencyEdge) -> TalosResult<()> {
+        self.record(&Record::Dependency(&edge))
+    }
+
+    fn error(&mut self, error: ErrorEntry) -> TalosResult<()> {
+        self.errors += 1;
+        self.record(&Record::Error(&error))
+    }
+
+    fn warning(&mut self, warning: WarningEntry) -> TalosResult<()> {
+        self.warnings += 1;
+        self.record(&Record::Warning(&warning))?;
+        self.warning_files.insert(warning.path);
+        Ok(())
+    }
+
+    fn skipped(&mut self, skipped: SkippedEntry) -> TalosResult<()> {
+        self.skipped += 1;
+        if skipped.reason == SkipReason::Generated {
+            self.generated += 1;
+        }
+        self.record(&Record::Skipped(&skipped))
+    }
+}
+
 fn ensure_parent_directory(path: &Path) -> TalosResult<()> {
     if let Some(parent) = path.parent() {
         if !parent.exists() {
@@ -52,9 +514,9 @@ impl TempFile {
         Ok(TempFile { path: temp_path })
     }
 
-    fn write_content(&self, content: &str) -> TalosResult<()> {
+    fn write_content(&self, content: &[u8]) -> TalosResult<()> {
         let mut file = File::create(&self.path)?;
-        file.write_all(content.as_bytes())?;
+        file.write_all(content)?;
         file.flush()?;
         Ok(())
     }
@@ -87,3 +549,86 @@ fn generate_temp_path(dir: &Path, base_name: &std::ffi::OsStr) -> PathBuf {
     let temp_name = format!(".{}.{}.{}.tmp", base_name.to_string_lossy(), pid, timestamp);
     dir.join(temp_name)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::sample_document;
+
+    /// Render `doc`, read it back with `parse`, and compare through JSON values
+    fn round_trip(writer: &dyn DocumentWriter, parse: impl Fn(&[u8]) -> Document) {
+        let doc = sample_document();
+        let parsed = parse(&writer.render(&doc).unwrap());
+        assert_eq!(
+            serde_json::to_value(&parsed).unwrap(),
+            serde_json::to_value(&doc).unwrap()
+        );
+    }
+
+    #[test]
+    fn xml_escape_keeps_legal_whitespace() {
+        assert_eq!(
+            xml_escape("a < b && c > d\r\n\tx"),
+            "a &lt; b &amp;&amp; c &gt; d\r\n\tx"
+        );
+        assert_eq!(
+            xml_escape("\u{0}\u{7}\u{1B}[0m\u{FFFF}"),
+            "\u{FFFD}\u{FFFD}\u{FFFD}[0m\u{FFFD}"
+        );
+        // Legal, if unusual, characters are kept
+        assert_eq!(xml_escape("\u{7F}\u{85}é"), "\u{7F}\u{85}é");
+        assert_eq!(xml_attr("say \"hi\"\r\n"), "say &quot;hi&quot;&#13;&#10;");
+    }
+
+    #[test]
+    fn xml_prompt_escapes_paths_and_signatures() {
+        let mut doc = sample_document();
+        let file = &mut doc.directories[0].files[0];
+        file.relative_file_path = "src/<a&b>\".ts".to_string();
+        file.signatures = vec!["function f<T>(a: T & U)\r".to_string()];
+
+        let out =
+            String::from_utf8(XmlPromptWriter { preamble: true }.render(&doc).unwrap()).unwrap();
+        assert!(out.starts_with("<project>\n<project_layout>\n"));
+        assert!(out.ends_with("</files>\n</project>\n"));
+        assert!(out.contains(r#"<file path="src/&lt;a&amp;b&gt;&quot;.ts">"#));
+        assert!(out.contains("function f&lt;T&gt;(a: T &amp; U)\r"));
+        assert!(out.contains(r#"<file path="packages/core/src/lib.rs" partial="true">"#));
+        assert!(!out.contains("<a&b>"));
+    }
+
+    #[test]
+    fn json_round_trips() {
+        for pretty in [true, false] {
+            round_trip(&JsonWriter { pretty }, |bytes| {
+                serde_json::from_slice(bytes).unwrap()
+            });
+        }
+    }
+
+    #[test]
+    fn yaml_round_trips() {
+        round_trip(&YamlWriter, |bytes| serde_yaml::from_slice(bytes).unwrap());
+    }
+
+    #[test]
+    fn toml_handles_an_empty_document() {
+        let out = TomlWriter.render(&Document::default()).unwrap();
+        let parsed: Document = toml::from_str(std::str::from_utf8(&out).unwrap()).unwrap();
+        assert!(parsed.directories.is_empty() && parsed.errors.is_empty());
+    }
+
+    #[test]
+    fn toml_round_trips() {
+        round_trip(&TomlWriter, |bytes| {
+            toml::from_str(std::str::from_utf8(bytes).unwrap()).unwrap()
+        });
+    }
+
+    #[test]
+    fn msgpack_round_trips() {
+        round_trip(&MsgpackWriter, |bytes| {
+            rmp_serde::from_slice(bytes).unwrap()
+        });
+    }
+}